@@ -0,0 +1,74 @@
+// src/assets.rs
+use crate::materials::MaterialError;
+use std::env;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AssetError {
+    #[error("asset not found: {0}")]
+    NotFound(PathBuf),
+    #[error("failed to read {path}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("failed to load image {path}: {message}")]
+    Image { path: PathBuf, message: String },
+    #[error("{path}: {source}")]
+    Materials { path: PathBuf, source: MaterialError },
+}
+
+// Directory every asset path (maze, manifest, textures) is resolved against
+pub struct AssetRoot {
+    pub root: PathBuf,
+}
+
+impl AssetRoot {
+    pub const ENV_VAR: &'static str = "MAZE_ASSET_ROOT";
+    const MARKER: &'static str = "materials.json";
+
+    // Pick the asset root, in order: `--assets <dir>` on the command line, the
+    // MAZE_ASSET_ROOT variable, the executable's directory or one of its parents
+    // (covers target/debug), and finally the current directory.
+    pub fn locate() -> Self {
+        let args: Vec<String> = env::args().collect();
+        if let Some(index) = args.iter().position(|arg| arg == "--assets")
+            && let Some(dir) = args.get(index + 1)
+        {
+            return Self { root: PathBuf::from(dir) };
+        }
+
+        if let Ok(dir) = env::var(Self::ENV_VAR) {
+            return Self { root: PathBuf::from(dir) };
+        }
+
+        if let Ok(exe) = env::current_exe() {
+            for dir in exe.ancestors().skip(1) {
+                if dir.join(Self::MARKER).is_file() {
+                    return Self { root: dir.to_path_buf() };
+                }
+            }
+        }
+
+        Self { root: PathBuf::from(".") }
+    }
+
+    pub fn resolve(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.root.join(relative)
+    }
+}
+
+// Per-user directory for files the game writes (settings, records, saves): %APPDATA% on Windows,
+// Application Support on macOS, $XDG_DATA_HOME or ~/.local/share elsewhere. None if the
+// platform gives no home to put it in.
+pub fn user_data_dir() -> Option<PathBuf> {
+    const APP_DIR: &str = "maze3d";
+
+    let base = if cfg!(windows) {
+        PathBuf::from(env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env::var_os("HOME")?).join("Library/Application Support")
+    } else if let Some(dir) = env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        PathBuf::from(dir)
+    } else {
+        PathBuf::from(env::var_os("HOME")?).join(".local/share")
+    };
+    Some(base.join(APP_DIR))
+}
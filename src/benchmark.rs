@@ -0,0 +1,41 @@
+// src/benchmark.rs
+use crate::exploration::ExplorationMap;
+use crate::framebuffer::Framebuffer;
+use crate::render3d::{Renderer3d, World};
+use raylib::prelude::*;
+use std::time::{Duration, Instant};
+
+const WIDTH: u32 = 1920;
+const HEIGHT: u32 = 1080;
+const FRAMES: u32 = 30;
+
+// Times the 3D pass offscreen at 1920x1080 on one thread and on `threads`, and checks
+// both produce the same image. Run the game with `--benchmark`.
+pub fn run(world: &World, threads: usize) {
+    let render = |threads: usize| {
+        let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT, Color::BLACK);
+        let mut exploration = ExplorationMap::new(world.maze, world.materials);
+        let mut renderer = Renderer3d::new(threads);
+
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            framebuffer.clear();
+            renderer.render(&mut framebuffer, world, &mut exploration);
+        }
+        (start.elapsed(), framebuffer.buffer)
+    };
+
+    println!("Rendering {} frames at {}x{}", FRAMES, WIDTH, HEIGHT);
+    let (single, single_image) = render(1);
+    report(1, single);
+    let (multi, multi_image) = render(threads);
+    report(threads, multi);
+
+    println!("Speedup: {:.2}x", single.as_secs_f64() / multi.as_secs_f64());
+    println!("Identical output: {}", if single_image == multi_image { "yes" } else { "NO" });
+}
+
+fn report(threads: usize, elapsed: Duration) {
+    let per_frame = elapsed.as_secs_f64() * 1000.0 / FRAMES as f64;
+    println!("{:>2} thread(s): {:>8.2} ms/frame", threads, per_frame);
+}
@@ -1,52 +1,200 @@
 // src/caster.rs
-use crate::framebuffer::Framebuffer;
-use crate::player::Player;
 use raylib::prelude::*;
 
-pub struct Intersect {
+// Side of the block the ray hit, named after the compass side in map space (north is up)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Face {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Face {
+    pub fn is_east_west(self) -> bool {
+        matches!(self, Face::East | Face::West)
+    }
+}
+
+// A cell boundary crossed by a ray
+#[derive(Debug, Clone, Copy)]
+pub struct Crossing {
     pub distance: f32,
-    pub impact: char,
+    pub col: usize,
+    pub row: usize,
+    pub face: Face, // Side of the entered cell the ray came in through
 }
 
-pub fn cast_ray(
-    framebuffer: &mut Framebuffer,
-    maze: &Vec<Vec<char>>,
-    player: &Player,
-    a: f32,
-    block_size: usize,
-    draw_line: bool,
-) -> Intersect {
-    let mut d = 0.0;
-    let max_distance = 1000.0;
+// Walks a ray from cell to cell with exact boundary distances, yielding every cell it
+// enters until it leaves the maze or passes `max_distance`. It does not stop at walls,
+// so the renderer can see over low ones.
+pub struct CellTrace<'a> {
+    maze: &'a [Vec<char>],
+    col: i64,
+    row: i64,
+    step_col: i64,
+    step_row: i64,
+    next_x: f32, // Ray distance to the next column boundary
+    next_y: f32, // Ray distance to the next row boundary
+    delta_x: f32,
+    delta_y: f32,
+    max_distance: f32,
+}
 
-    framebuffer.set_current_color(Color::WHITESMOKE);
+impl<'a> CellTrace<'a> {
+    pub fn new(
+        maze: &'a [Vec<char>],
+        origin: Vector2,
+        a: f32,
+        block_size: usize,
+        max_distance: f32,
+    ) -> Self {
+        let block = block_size as f32;
+        let (dx, dy) = (a.cos(), a.sin());
+        let col = (origin.x / block).floor() as i64;
+        let row = (origin.y / block).floor() as i64;
 
-    while d < max_distance {
-        let cos = d * a.cos();
-        let sin = d * a.sin();
-        let x = player.pos.x + cos;
-        let y = player.pos.y + sin;
+        // Distance along the ray to the first boundary on each axis, then between boundaries
+        let first = |position: f32, cell: i64, direction: f32| {
+            if direction > 0.0 {
+                ((cell + 1) as f32 * block - position) / direction
+            } else if direction < 0.0 {
+                (cell as f32 * block - position) / direction
+            } else {
+                f32::INFINITY
+            }
+        };
 
-        if x < 0.0 || y < 0.0 || x >= framebuffer.width as f32 || y >= framebuffer.height as f32 {
-            return Intersect { distance: d, impact: ' ' };
+        Self {
+            maze,
+            col,
+            row,
+            step_col: if dx < 0.0 { -1 } else { 1 },
+            step_row: if dy < 0.0 { -1 } else { 1 },
+            next_x: first(origin.x, col, dx),
+            next_y: first(origin.y, row, dy),
+            delta_x: if dx == 0.0 { f32::INFINITY } else { block / dx.abs() },
+            delta_y: if dy == 0.0 { f32::INFINITY } else { block / dy.abs() },
+            max_distance,
         }
+    }
+}
 
-        let i = (x as usize) / block_size;
-        let j = (y as usize) / block_size;
+impl Iterator for CellTrace<'_> {
+    type Item = Crossing;
 
-        if j >= maze.len() || i >= maze[0].len() || (maze[j][i] != ' ' && maze[j][i] != 'p') {
-            return Intersect {
-                distance: d,
-                impact: if j >= maze.len() || i >= maze[0].len() { ' ' } else { maze[j][i] },
-            };
+    fn next(&mut self) -> Option<Crossing> {
+        let (distance, face) = if self.next_x < self.next_y {
+            self.col += self.step_col;
+            let distance = self.next_x;
+            self.next_x += self.delta_x;
+            (distance, if self.step_col > 0 { Face::West } else { Face::East })
+        } else {
+            self.row += self.step_row;
+            let distance = self.next_y;
+            self.next_y += self.delta_y;
+            (distance, if self.step_row > 0 { Face::North } else { Face::South })
+        };
+
+        if distance > self.max_distance || self.col < 0 || self.row < 0 {
+            return None;
+        }
+        let (col, row) = (self.col as usize, self.row as usize);
+        if row >= self.maze.len() || col >= self.maze[row].len() {
+            return None;
         }
 
-        if draw_line {
-            framebuffer.set_pixel(x as u32, y as u32);
+        Some(Crossing { distance, col, row, face })
+    }
+}
+
+// Where a ray meets a thin slab across the middle of a cell
+pub struct SlabHit {
+    pub distance: f32,
+    pub face: Face,
+    pub offset: f32, // Position along the slab, 0 to 1
+}
+
+// Partial-cell hit against a slab through the centre of cell (col, row). `along_x` slabs run
+// east-west; others run north-south. Only hits between `enter` and `exit` count, i.e. while
+// the ray is inside the cell.
+#[allow(clippy::too_many_arguments)]
+pub fn slab_hit(
+    origin: Vector2,
+    a: f32,
+    col: usize,
+    row: usize,
+    along_x: bool,
+    block_size: usize,
+    enter: f32,
+    exit: f32,
+) -> Option<SlabHit> {
+    let block = block_size as f32;
+    let (dx, dy) = (a.cos(), a.sin());
+
+    let (distance, face, along) = if along_x {
+        if dy == 0.0 {
+            return None;
+        }
+        let distance = ((row as f32 + 0.5) * block - origin.y) / dy;
+        let face = if dy > 0.0 { Face::North } else { Face::South };
+        (distance, face, origin.x + distance * dx - col as f32 * block)
+    } else {
+        if dx == 0.0 {
+            return None;
+        }
+        let distance = ((col as f32 + 0.5) * block - origin.x) / dx;
+        let face = if dx > 0.0 { Face::West } else { Face::East };
+        (distance, face, origin.y + distance * dy - row as f32 * block)
+    };
+
+    let offset = along / block;
+    if distance < enter || distance > exit || !(0.0..1.0).contains(&offset) {
+        return None;
+    }
+    Some(SlabHit { distance, face, offset })
+}
+
+// Where a ray enters an axis-aligned box from `min` to `max`, such as a wall partway
+// between cells. Only hits between `enter` and `exit` count. The offset runs along x on
+// north and south faces and along y on east and west ones.
+pub fn box_hit(
+    origin: Vector2,
+    a: f32,
+    min: Vector2,
+    max: Vector2,
+    enter: f32,
+    exit: f32,
+) -> Option<SlabHit> {
+    let (dx, dy) = (a.cos(), a.sin());
+
+    // Ray distances to the box's near and far planes on one axis
+    let span = |position: f32, direction: f32, low: f32, high: f32| {
+        if direction == 0.0 {
+            if (low..high).contains(&position) {
+                (f32::NEG_INFINITY, f32::INFINITY)
+            } else {
+                (f32::INFINITY, f32::NEG_INFINITY)
+            }
+        } else {
+            let (t0, t1) = ((low - position) / direction, (high - position) / direction);
+            (t0.min(t1), t0.max(t1))
         }
+    };
+    let (near_x, far_x) = span(origin.x, dx, min.x, max.x);
+    let (near_y, far_y) = span(origin.y, dy, min.y, max.y);
 
-        d += 1.0;
+    let distance = near_x.max(near_y);
+    if distance > far_x.min(far_y) || distance < enter.max(0.0) || distance > exit {
+        return None;
     }
 
-    Intersect { distance: max_distance, impact: ' ' }
+    let (face, offset) = if near_x > near_y {
+        let face = if dx > 0.0 { Face::West } else { Face::East };
+        (face, (origin.y + distance * dy - min.y) / (max.y - min.y))
+    } else {
+        let face = if dy > 0.0 { Face::North } else { Face::South };
+        (face, (origin.x + distance * dx - min.x) / (max.x - min.x))
+    };
+    Some(SlabHit { distance, face, offset: offset.clamp(0.0, 1.0) })
 }
@@ -0,0 +1,128 @@
+// src/checkpoints.rs
+use crate::materials::MaterialRegistry;
+use crate::pathfinding::Cell;
+use crate::player::Player;
+use std::collections::HashSet;
+
+// Checkpoint cells and the split times for the current run. Each split is the level time
+// a checkpoint was first reached, in the order they were reached, and the finish time is
+// the last split. Splits are compared by position against the fastest finished run.
+pub struct Checkpoints {
+    cells: HashSet<Cell>,
+    reached: HashSet<Cell>,
+    splits: Vec<f32>,
+    finished: bool,
+    best: Option<Vec<f32>>,    // Splits of the fastest finished run
+    compare: Option<Vec<f32>>, // `best` as it was when this run started
+    block_size: usize,
+}
+
+impl Checkpoints {
+    pub fn new(maze: &[Vec<char>], materials: &MaterialRegistry, block_size: usize) -> Self {
+        let mut cells = HashSet::new();
+        for (row, glyphs) in maze.iter().enumerate() {
+            for (col, &glyph) in glyphs.iter().enumerate() {
+                if materials.get(glyph).checkpoint {
+                    cells.insert((col, row));
+                }
+            }
+        }
+
+        Self {
+            cells,
+            reached: HashSet::new(),
+            splits: Vec::new(),
+            finished: false,
+            best: None,
+            compare: None,
+            block_size,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.reached.clear();
+        self.splits.clear();
+        self.finished = false;
+        self.compare = self.best.clone();
+    }
+
+    // Record a split if the player has stepped onto a checkpoint for the first time.
+    // Returns that checkpoint's cell.
+    pub fn update(&mut self, player: &Player, level_time: f32) -> Option<Cell> {
+        let cell =
+            (player.pos.x as usize / self.block_size, player.pos.y as usize / self.block_size);
+        if !self.cells.contains(&cell) || !self.reached.insert(cell) {
+            return None;
+        }
+        self.splits.push(level_time);
+        Some(cell)
+    }
+
+    // Record the finish time, and keep this run's splits if it beat the best
+    pub fn finish(&mut self, level_time: f32) {
+        self.splits.push(level_time);
+        self.finished = true;
+        let best_time = self.best.as_ref().and_then(|best| best.last());
+        if best_time.is_none_or(|&best| level_time < best) {
+            self.best = Some(self.splits.clone());
+        }
+    }
+
+    // Compare against splits kept from an earlier session
+    pub fn set_best(&mut self, splits: Vec<f32>) {
+        if !splits.is_empty() {
+            self.best = Some(splits.clone());
+            self.compare = Some(splits);
+        }
+    }
+
+    pub fn splits(&self) -> &[f32] {
+        &self.splits
+    }
+
+    pub fn reached_cells(&self) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = self.reached.iter().copied().collect();
+        cells.sort();
+        cells
+    }
+
+    // Carry on a run from a saved game
+    pub fn restore(&mut self, reached: &[Cell], splits: &[f32]) {
+        self.reached = reached.iter().copied().filter(|cell| self.cells.contains(cell)).collect();
+        self.splits = splits.to_vec();
+        self.finished = false;
+    }
+
+    pub fn total(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn reached(&self) -> usize {
+        self.reached.len()
+    }
+
+    // Latest split against the same split of the best run; negative is ahead. A finished run
+    // compares finish times.
+    pub fn delta(&self) -> Option<f32> {
+        let compare = self.compare.as_ref()?;
+        let split = *self.splits.last()?;
+        let best = if self.finished {
+            compare.last()
+        } else {
+            // The best run's last split is its finish, not a checkpoint
+            compare[..compare.len().saturating_sub(1)].get(self.splits.len() - 1)
+        };
+        best.map(|best| split - best)
+    }
+}
+
+// Level time as minutes, seconds and hundredths
+pub fn format_time(seconds: f32) -> String {
+    let hundredths = (seconds.max(0.0) * 100.0).round() as u32;
+    format!("{}:{:02}.{:02}", hundredths / 6000, hundredths / 100 % 60, hundredths % 100)
+}
+
+// Difference from a best time, signed: -1.25 is ahead, +0.40 behind
+pub fn format_delta(delta: f32) -> String {
+    format!("{}{:.2}", if delta < 0.0 { '-' } else { '+' }, delta.abs())
+}
@@ -0,0 +1,204 @@
+// src/doors.rs
+use crate::caster::{CellTrace, SlabHit, slab_hit};
+use crate::inventory::Inventory;
+use crate::materials::MaterialRegistry;
+use crate::pathfinding::Cell;
+use crate::player::Player;
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// A sliding door in the middle of its cell
+pub struct Door {
+    pub along_x: bool,        // Slab runs east-west, so the passage runs north-south
+    pub openness: f32,        // 0 closed, 1 fully slid into the wall
+    pub lock: Option<String>, // Key needed to open it
+    opening: bool,
+    open_time: f32, // Seconds spent fully open
+}
+
+impl Door {
+    // Mostly open doors let the player through
+    pub fn is_passable(&self) -> bool {
+        self.openness >= 0.9
+    }
+
+    pub fn can_open(&self, inventory: &Inventory) -> bool {
+        self.lock.as_ref().is_none_or(|key| inventory.has(key))
+    }
+}
+
+// How far a door has moved, for save games
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DoorState {
+    pub openness: f32,
+    pub opening: bool,
+    pub open_time: f32,
+}
+
+// Every door in the maze, keyed by (col, row)
+pub struct Doors {
+    doors: HashMap<(usize, usize), Door>,
+    block_size: usize,
+}
+
+impl Doors {
+    const SPEED: f32 = 1.5; // Openness per second
+    const STAY_OPEN: f32 = 3.0; // Seconds before closing again
+    const AUTO_OPEN_DISTANCE: f32 = 0.9; // In blocks from the door's centre
+    const USE_REACH: f32 = 1.5; // In blocks
+
+    pub fn new(maze: &[Vec<char>], materials: &MaterialRegistry, block_size: usize) -> Self {
+        let mut doors = HashMap::new();
+        let blocks = |col: usize, row: Option<usize>| {
+            row.and_then(|row| maze.get(row).and_then(|cells| cells.get(col)))
+                .is_some_and(|&cell| materials.blocks_sight(cell))
+        };
+
+        for (row, cells) in maze.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                let material = materials.get(cell);
+                if !material.door {
+                    continue;
+                }
+                // Doors span the passage: east-west unless walled in to the north and south
+                let along_x = !(blocks(col, row.checked_sub(1)) && blocks(col, Some(row + 1)));
+                doors.insert(
+                    (col, row),
+                    Door {
+                        along_x,
+                        openness: 0.0,
+                        lock: material.lock.clone(),
+                        opening: false,
+                        open_time: 0.0,
+                    },
+                );
+            }
+        }
+
+        Self { doors, block_size }
+    }
+
+    pub fn reset(&mut self) {
+        for door in self.doors.values_mut() {
+            door.openness = 0.0;
+            door.opening = false;
+            door.open_time = 0.0;
+        }
+    }
+
+    pub fn states(&self) -> Vec<(Cell, DoorState)> {
+        let state = |door: &Door| DoorState {
+            openness: door.openness,
+            opening: door.opening,
+            open_time: door.open_time,
+        };
+        self.doors.iter().map(|(&cell, door)| (cell, state(door))).collect()
+    }
+
+    // Put doors back as they were saved; cells without a door are skipped
+    pub fn restore(&mut self, states: &[(Cell, DoorState)]) {
+        for (cell, state) in states {
+            if let Some(door) = self.doors.get_mut(cell) {
+                door.openness = state.openness.clamp(0.0, 1.0);
+                door.opening = state.opening;
+                door.open_time = state.open_time;
+            }
+        }
+    }
+
+    pub fn get(&self, col: usize, row: usize) -> Option<&Door> {
+        self.doors.get(&(col, row))
+    }
+
+    // Whether the player may stand at this point as far as doors are concerned
+    pub fn is_passable(&self, x: f32, y: f32) -> bool {
+        let cell = (x as usize / self.block_size, y as usize / self.block_size);
+        self.doors.get(&cell).is_none_or(Door::is_passable)
+    }
+
+    // Open the first door in front of the player within reach. Returns the key it needs
+    // when it is locked and the player doesn't have it.
+    pub fn use_door(
+        &mut self,
+        player: &Player,
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        inventory: &Inventory,
+    ) -> Option<&str> {
+        let reach = Self::USE_REACH * self.block_size as f32;
+        for crossing in CellTrace::new(maze, player.pos, player.a, self.block_size, reach) {
+            let cell = (crossing.col, crossing.row);
+            if self.doors.contains_key(&cell) {
+                return self.open(cell, inventory);
+            }
+            if materials.blocks_sight(maze[crossing.row][crossing.col]) {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn open(&mut self, cell: (usize, usize), inventory: &Inventory) -> Option<&str> {
+        let door = self.doors.get_mut(&cell)?;
+        if !door.can_open(inventory) {
+            return door.lock.as_deref();
+        }
+        door.opening = true;
+        door.open_time = 0.0;
+        None
+    }
+
+    // Slide doors, open the ones the player walks up to and close them after a while.
+    // A door never closes on the player; it bounces back open instead. Locked doors only
+    // open by themselves for the player holding their key.
+    pub fn update(&mut self, dt: f32, player: &Player, inventory: &Inventory) {
+        let block = self.block_size as f32;
+        let player_cell =
+            (player.pos.x as usize / self.block_size, player.pos.y as usize / self.block_size);
+
+        for (&(col, row), door) in self.doors.iter_mut() {
+            let center = Vector2::new((col as f32 + 0.5) * block, (row as f32 + 0.5) * block);
+            let near = (player.pos - center).length() < Self::AUTO_OPEN_DISTANCE * block
+                && door.can_open(inventory);
+            let occupied = player_cell == (col, row);
+
+            if near || (occupied && !door.opening) {
+                door.opening = true;
+                door.open_time = 0.0;
+            }
+
+            if door.opening {
+                door.openness = (door.openness + Self::SPEED * dt).min(1.0);
+                if door.openness >= 1.0 {
+                    door.open_time += dt;
+                    if door.open_time >= Self::STAY_OPEN && !occupied {
+                        door.opening = false;
+                    }
+                }
+            } else {
+                door.openness = (door.openness - Self::SPEED * dt).max(0.0);
+            }
+        }
+    }
+
+    // Where a ray inside a door's cell meets the closed part of the slab, with the texture
+    // U measured from the door's leading edge so the texture slides with it
+    pub fn hit(
+        &self,
+        origin: Vector2,
+        a: f32,
+        col: usize,
+        row: usize,
+        enter: f32,
+        exit: f32,
+    ) -> Option<(SlabHit, f32)> {
+        let door = self.doors.get(&(col, row))?;
+        let hit = slab_hit(origin, a, col, row, door.along_x, self.block_size, enter, exit)?;
+        if hit.offset < door.openness {
+            return None;
+        }
+        let u = hit.offset - door.openness;
+        Some((hit, u))
+    }
+}
@@ -0,0 +1,72 @@
+// src/exploration.rs
+use crate::materials::MaterialRegistry;
+use serde::{Deserialize, Serialize};
+
+// Which maze cells the player has seen, filled in by the rays cast each frame
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExplorationMap {
+    pub width: usize,
+    pub height: usize,
+    discovered: Vec<bool>,
+    // Cells the player can stand in, which are what the explored share counts. Walls are
+    // still marked when seen, for the maps. Not saved; it comes from the maze.
+    #[serde(skip)]
+    walkable: Vec<bool>,
+}
+
+impl ExplorationMap {
+    pub fn new(maze: &[Vec<char>], materials: &MaterialRegistry) -> Self {
+        let width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+        let height = maze.len();
+
+        let mut walkable = vec![false; width * height];
+        for (row, cells) in maze.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                walkable[row * width + col] = materials.is_walkable(cell);
+            }
+        }
+
+        Self { width, height, discovered: vec![false; width * height], walkable }
+    }
+
+    pub fn reset(&mut self) {
+        for cell in self.discovered.iter_mut() {
+            *cell = false;
+        }
+    }
+
+    pub fn mark(&mut self, col: usize, row: usize) {
+        if col < self.width && row < self.height {
+            self.discovered[row * self.width + col] = true;
+        }
+    }
+
+    // Add everything another map has seen, e.g. one filled by a render thread
+    pub fn merge(&mut self, other: &ExplorationMap) {
+        for (cell, &seen) in self.discovered.iter_mut().zip(&other.discovered) {
+            *cell |= seen;
+        }
+    }
+
+    pub fn is_discovered(&self, col: usize, row: usize) -> bool {
+        col < self.width && row < self.height && self.discovered[row * self.width + col]
+    }
+
+    // Walkable cells seen so far
+    pub fn discovered_count(&self) -> usize {
+        self.discovered
+            .iter()
+            .zip(&self.walkable)
+            .filter(|&(&seen, &walkable)| seen && walkable)
+            .count()
+    }
+
+    // Share of the walkable cells seen so far, from 0 to 100
+    pub fn percent_explored(&self) -> f32 {
+        let total = self.walkable.iter().filter(|&&walkable| walkable).count();
+        if total == 0 {
+            return 0.0;
+        }
+        self.discovered_count() as f32 * 100.0 / total as f32
+    }
+}
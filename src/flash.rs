@@ -0,0 +1,39 @@
+// src/flash.rs
+use crate::framebuffer::Framebuffer;
+use raylib::prelude::*;
+
+// Full-screen colour flash that fades out, tinted over the finished frame
+pub struct ScreenFlash {
+    color: Color,
+    strength: f32, // 0 none, 1 solid colour
+}
+
+impl ScreenFlash {
+    const FADE: f32 = 2.0; // Strength lost per second
+
+    pub fn new() -> Self {
+        Self { color: Color::RED, strength: 0.0 }
+    }
+
+    pub fn reset(&mut self) {
+        self.strength = 0.0;
+    }
+
+    // Start a flash, or keep the current one going if it is stronger
+    pub fn trigger(&mut self, color: Color, strength: f32) {
+        if strength >= self.strength {
+            self.color = color;
+            self.strength = strength.min(1.0);
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.strength = (self.strength - Self::FADE * dt).max(0.0);
+    }
+
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if self.strength > 0.0 {
+            framebuffer.tint(self.color, self.strength);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// src/fog.rs
+use raylib::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FogCurve {
+    Linear,
+    Exponential,
+    ExponentialSquared,
+}
+
+// Distance fog blended over walls, floors, ceilings and sprites
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct Fog {
+    pub color: [u8; 3],
+    pub start: f32, // Distance where fog begins
+    pub end: f32,   // Distance where fog reaches full opacity
+    pub curve: FogCurve,
+    pub density: f32, // Exponential curves only
+    pub opacity: f32, // Strongest the fog gets, 0 disables it
+}
+
+impl Default for Fog {
+    // Matches the original darkening: black, linear up to 500 units, capped at 80%
+    fn default() -> Self {
+        Self {
+            color: [0, 0, 0],
+            start: 0.0,
+            end: 500.0,
+            curve: FogCurve::Linear,
+            density: 0.004,
+            opacity: 0.8,
+        }
+    }
+}
+
+impl Fog {
+    // How much fog covers a surface at `distance`, from 0 (clear) to `opacity`. Every curve
+    // is scaled to reach full fog exactly at `end`, so there is no step there.
+    pub fn amount(&self, distance: f32) -> f32 {
+        let depth = (distance - self.start).max(0.0);
+        let full = (self.end - self.start).max(f32::EPSILON);
+        let amount = if distance >= self.end {
+            1.0
+        } else {
+            self.curve(depth) / self.curve(full).max(f32::EPSILON)
+        };
+        amount.clamp(0.0, 1.0) * self.opacity.clamp(0.0, 1.0)
+    }
+
+    // Unscaled fog at `depth` past `start`
+    fn curve(&self, depth: f32) -> f32 {
+        match self.curve {
+            FogCurve::Linear => depth,
+            FogCurve::Exponential => 1.0 - (-self.density * depth).exp(),
+            FogCurve::ExponentialSquared => 1.0 - (-(self.density * depth).powi(2)).exp(),
+        }
+    }
+
+    pub fn apply(&self, color: Color, distance: f32) -> Color {
+        let amount = self.amount(distance);
+        let [r, g, b] = self.color;
+        let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount) as u8;
+
+        Color::new(mix(color.r, r), mix(color.g, g), mix(color.b, b), color.a)
+    }
+}
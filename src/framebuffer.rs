@@ -1,12 +1,44 @@
 // src/framebuffer.rs
 use raylib::prelude::*;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClipRegion {
+    Rect { x: i32, y: i32, width: i32, height: i32 },
+    Circle { center_x: i32, center_y: i32, radius: i32 },
+}
+
+impl ClipRegion {
+    pub fn contains(&self, px: i32, py: i32) -> bool {
+        match *self {
+            ClipRegion::Rect { x, y, width, height } => {
+                px >= x && py >= y && px < x + width && py < y + height
+            }
+            ClipRegion::Circle { center_x, center_y, radius } => {
+                let dx = px - center_x;
+                let dy = py - center_y;
+                dx * dx + dy * dy <= radius * radius
+            }
+        }
+    }
+
+    // Bounding box as (x, y, width, height)
+    pub fn bounds(&self) -> (i32, i32, i32, i32) {
+        match *self {
+            ClipRegion::Rect { x, y, width, height } => (x, y, width, height),
+            ClipRegion::Circle { center_x, center_y, radius } => {
+                (center_x - radius, center_y - radius, radius * 2 + 1, radius * 2 + 1)
+            }
+        }
+    }
+}
+
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
     pub buffer: Vec<Color>,
     pub background_color: Color,
     pub current_color: Color,
+    pub clip: Option<ClipRegion>,
 }
 
 impl Framebuffer {
@@ -14,7 +46,7 @@ impl Framebuffer {
         let size = (width * height) as usize;
         let buffer = vec![background_color; size];
 
-        Self { width, height, buffer, background_color, current_color: Color::WHITE }
+        Self { width, height, buffer, background_color, current_color: Color::WHITE, clip: None }
     }
 
     pub fn clear(&mut self) {
@@ -24,6 +56,12 @@ impl Framebuffer {
     }
 
     pub fn set_pixel(&mut self, x: u32, y: u32) {
+        if let Some(clip) = self.clip
+            && !clip.contains(x as i32, y as i32)
+        {
+            return;
+        }
+
         if x < self.width && y < self.height {
             let index = (y * self.width + x) as usize;
             if index < self.buffer.len() {
@@ -36,6 +74,15 @@ impl Framebuffer {
         self.current_color = color;
     }
 
+    // Restrict drawing to a region until clear_clip is called
+    pub fn set_clip(&mut self, clip: ClipRegion) {
+        self.clip = Some(clip);
+    }
+
+    pub fn clear_clip(&mut self) {
+        self.clip = None;
+    }
+
     pub fn set_background_color(&mut self, color: Color) {
         self.background_color = color;
     }
@@ -50,6 +97,32 @@ impl Framebuffer {
         }
     }
 
+    // Stretch another framebuffer over this whole one, nearest neighbour
+    pub fn blit_scaled(&mut self, source: &Framebuffer) {
+        for y in 0..self.height {
+            let source_y = (y as u64 * source.height as u64 / self.height as u64) as u32;
+            for x in 0..self.width {
+                let source_x = (x as u64 * source.width as u64 / self.width as u64) as u32;
+                let index = (y * self.width + x) as usize;
+                self.buffer[index] = source.buffer[(source_y * source.width + source_x) as usize];
+            }
+        }
+    }
+
+    // Blend every pixel towards `color` by `amount` (0 to 1)
+    pub fn tint(&mut self, color: Color, amount: f32) {
+        let amount = amount.clamp(0.0, 1.0);
+        let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount) as u8;
+        for pixel in &mut self.buffer {
+            *pixel = Color::new(
+                mix(pixel.r, color.r),
+                mix(pixel.g, color.g),
+                mix(pixel.b, color.b),
+                pixel.a,
+            );
+        }
+    }
+
     // New methods for minimap functionality
     pub fn draw_rectangle(&mut self, x: i32, y: i32, width: i32, height: i32) {
         for dy in 0..height {
@@ -106,4 +179,55 @@ impl Framebuffer {
             }
         }
     }
+
+    pub fn draw_circle_outline(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius: i32,
+        thickness: i32,
+    ) {
+        let inner = (radius - thickness).max(0);
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                let dist_sq = x * x + y * y;
+                if dist_sq <= radius * radius && dist_sq >= inner * inner {
+                    let px = center_x + x;
+                    let py = center_y + y;
+                    if px >= 0 && py >= 0 {
+                        self.set_pixel(px as u32, py as u32);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn fill_triangle(&mut self, v0: (f32, f32), v1: (f32, f32), v2: (f32, f32)) {
+        let min_x = v0.0.min(v1.0).min(v2.0).floor().max(0.0) as i32;
+        let max_x = v0.0.max(v1.0).max(v2.0).ceil() as i32;
+        let min_y = v0.1.min(v1.1).min(v2.1).floor().max(0.0) as i32;
+        let max_y = v0.1.max(v1.1).max(v2.1).ceil() as i32;
+
+        // Signed area tells us the winding so both orientations fill
+        let edge = |a: (f32, f32), b: (f32, f32), px: f32, py: f32| {
+            (b.0 - a.0) * (py - a.1) - (b.1 - a.1) * (px - a.0)
+        };
+        let area = edge(v0, v1, v2.0, v2.1);
+        if area == 0.0 {
+            return;
+        }
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let px = x as f32 + 0.5;
+                let py = y as f32 + 0.5;
+                let w0 = edge(v1, v2, px, py) * area;
+                let w1 = edge(v2, v0, px, py) * area;
+                let w2 = edge(v0, v1, px, py) * area;
+                if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
+                    self.set_pixel(x as u32, y as u32);
+                }
+            }
+        }
+    }
 }
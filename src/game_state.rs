@@ -1,23 +1,162 @@
 // src/game_state.rs
+use crate::checkpoints::{Checkpoints, format_delta, format_time};
+use crate::doors::Doors;
+use crate::exploration::ExplorationMap;
+use crate::inventory::Inventory;
+use crate::materials::MaterialRegistry;
+use crate::player::Player;
+use crate::pushwalls::Pushwalls;
+use crate::teleporters::Teleporters;
+use crate::waypoints::Waypoints;
+use raylib::prelude::*;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameState {
     Menu,
     Playing,
+    Paused,
+    Dead,
     Victory,
 }
 
+// Where the player comes back after dying
+#[derive(Debug, Clone, Copy)]
+pub struct RespawnPoint {
+    pub pos: Vector2,
+    pub a: f32,
+    pub elevation: f32,
+}
+
+impl RespawnPoint {
+    pub fn of(player: &Player) -> Self {
+        Self { pos: player.pos, a: player.a, elevation: player.elevation }
+    }
+}
+
+// Tallies for the current run, shown when the level is complete
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LevelStats {
+    pub secrets_found: usize,
+    pub secrets_total: usize,
+}
+
 pub struct GameManager {
     pub state: GameState,
     pub level_time: f32,
+    pub exploration: ExplorationMap,
+    pub waypoints: Waypoints,
+    pub doors: Doors,
+    pub inventory: Inventory,
+    pub teleporters: Teleporters,
+    pub pushwalls: Pushwalls,
+    pub checkpoints: Checkpoints,
+    pub stats: LevelStats,
+    pub respawn: RespawnPoint, // Last checkpoint, or the level start
+    start: RespawnPoint,
+    message: Option<(String, f32)>, // HUD text and the level time it disappears
 }
 
 impl GameManager {
-    pub fn new() -> Self {
-        Self { state: GameState::Menu, level_time: 0.0 }
+    pub fn new(
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        player: &Player,
+        block_size: usize,
+    ) -> Self {
+        let pushwalls = Pushwalls::new(maze, materials, block_size);
+        let stats = LevelStats { secrets_total: pushwalls.total(), ..LevelStats::default() };
+        Self {
+            state: GameState::Menu,
+            level_time: 0.0,
+            exploration: ExplorationMap::new(maze, materials),
+            waypoints: Waypoints::new(),
+            doors: Doors::new(maze, materials, block_size),
+            inventory: Inventory::new(),
+            teleporters: Teleporters::new(maze, materials),
+            pushwalls,
+            checkpoints: Checkpoints::new(maze, materials, block_size),
+            stats,
+            respawn: RespawnPoint::of(player),
+            start: RespawnPoint::of(player),
+            message: None,
+        }
     }
 
     pub fn reset(&mut self) {
         self.state = GameState::Playing;
         self.level_time = 0.0;
+        self.exploration.reset();
+        self.waypoints.clear();
+        self.doors.reset();
+        self.inventory.reset();
+        self.teleporters.reset();
+        self.pushwalls.reset();
+        self.checkpoints.reset();
+        self.stats.secrets_found = 0;
+        self.respawn = self.start;
+        self.message = None;
+    }
+
+    // Bring the player back at the respawn point with full health. Keys, doors and the
+    // timer carry on from where they were.
+    pub fn respawn(&mut self, player: &mut Player) {
+        player.pos = self.respawn.pos;
+        player.a = self.respawn.a;
+        player.elevation = self.respawn.elevation;
+        player.pitch = 0.0;
+        player.health = Player::MAX_HEALTH;
+        self.teleporters.reset();
+        self.state = GameState::Playing;
+    }
+
+    // Split the timer when the player reaches a new checkpoint, and respawn there from now on.
+    // Returns whether one was reached.
+    pub fn update_checkpoints(&mut self, player: &Player, block_size: usize) -> bool {
+        let Some((col, row)) = self.checkpoints.update(player, self.level_time) else {
+            return false;
+        };
+        let block = block_size as f32;
+        self.respawn = RespawnPoint {
+            pos: Vector2::new((col as f32 + 0.5) * block, (row as f32 + 0.5) * block),
+            a: player.a,
+            elevation: player.elevation,
+        };
+
+        let mut text = format!(
+            "Checkpoint {}/{}  {}",
+            self.checkpoints.reached(),
+            self.checkpoints.total(),
+            format_time(self.level_time)
+        );
+        if let Some(delta) = self.checkpoints.delta() {
+            text += &format!("  ({})", format_delta(delta));
+        }
+        self.notify(text);
+        true
+    }
+
+    // The goal has been reached
+    pub fn finish(&mut self) {
+        self.checkpoints.finish(self.level_time);
+        self.state = GameState::Victory;
+    }
+
+    // A pushwall has been pushed for the first time
+    pub fn find_secret(&mut self) {
+        self.stats.secrets_found += 1;
+        self.notify("Secret found!");
+    }
+
+    // Show a short message in the HUD for a few seconds
+    pub fn notify(&mut self, text: impl Into<String>) {
+        const MESSAGE_SECONDS: f32 = 2.5;
+        self.message = Some((text.into(), self.level_time + MESSAGE_SECONDS));
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message
+            .as_ref()
+            .filter(|(_, until)| self.level_time < *until)
+            .map(|(text, _)| text.as_str())
     }
 }
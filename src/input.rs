@@ -1,16 +1,42 @@
 // src/input.rs
+use crate::doors::Doors;
+use crate::map_overlay::MapOverlay;
+use crate::materials::MaterialRegistry;
+use crate::minimap::{Minimap, MinimapFit};
+use crate::pause_menu::{PauseAction, PauseMenu, PausePage};
 use crate::player::Player;
+use crate::pushwalls::Pushwalls;
+use crate::waypoints::Waypoints;
 use raylib::prelude::*;
 use std::f32::consts::PI;
 
 pub fn process_events(
     rl: &RaylibHandle,
     player: &mut Player,
-    maze: &Vec<Vec<char>>,
+    maze: &[Vec<char>],
+    materials: &MaterialRegistry,
+    doors: &Doors,
+    pushwalls: &Pushwalls,
     block_size: usize,
 ) {
     const MOVE_SPEED: f32 = 3.0;
     const ROTATION_SPEED: f32 = PI / 60.0;
+    const LOOK_SPEED: f32 = 0.03;
+    const MOUSE_LOOK_SPEED: f32 = 0.005;
+
+    // Look up and down: Page Up/Down, or drag with the right mouse button. Home recentres.
+    if rl.is_key_down(KeyboardKey::KEY_PAGE_UP) {
+        player.look(LOOK_SPEED);
+    }
+    if rl.is_key_down(KeyboardKey::KEY_PAGE_DOWN) {
+        player.look(-LOOK_SPEED);
+    }
+    if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT) {
+        player.look(-rl.get_mouse_delta().y * MOUSE_LOOK_SPEED);
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_HOME) {
+        player.pitch = 0.0;
+    }
 
     if rl.is_key_down(KeyboardKey::KEY_LEFT) || rl.is_key_down(KeyboardKey::KEY_A) {
         player.a -= ROTATION_SPEED;
@@ -23,7 +49,7 @@ pub fn process_events(
         let new_x = player.pos.x + MOVE_SPEED * player.a.cos();
         let new_y = player.pos.y + MOVE_SPEED * player.a.sin();
 
-        if is_valid_position(new_x, new_y, maze, block_size) {
+        if is_valid_position(new_x, new_y, maze, materials, doors, pushwalls, block_size) {
             player.pos.x = new_x;
             player.pos.y = new_y;
         } else {
@@ -33,7 +59,7 @@ pub fn process_events(
         let new_x = player.pos.x - MOVE_SPEED * player.a.cos();
         let new_y = player.pos.y - MOVE_SPEED * player.a.sin();
 
-        if is_valid_position(new_x, new_y, maze, block_size) {
+        if is_valid_position(new_x, new_y, maze, materials, doors, pushwalls, block_size) {
             player.pos.x = new_x;
             player.pos.y = new_y;
         } else {
@@ -41,7 +67,112 @@ pub fn process_events(
     }
 }
 
-fn is_valid_position(x: f32, y: f32, maze: &Vec<Vec<char>>, block_size: usize) -> bool {
+pub fn process_minimap_events(rl: &RaylibHandle, minimap: &mut Minimap, maze: &[Vec<char>]) {
+    const ZOOM_STEP: f32 = 1.25;
+
+    // Switch between the overview and the player-centred minimap
+    if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+        minimap.toggle_mode();
+    }
+
+    // Overview: fit the whole maze or clip at a fixed scale
+    if rl.is_key_pressed(KeyboardKey::KEY_F) {
+        let fit = match minimap.fit {
+            MinimapFit::Fit => MinimapFit::Clip,
+            MinimapFit::Clip => MinimapFit::Fit,
+        };
+        minimap.set_fit(fit, maze);
+    }
+
+    // Follow mode: rotate with the player, viewport shape and zoom
+    if rl.is_key_pressed(KeyboardKey::KEY_R) {
+        minimap.rotate = !minimap.rotate;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_C) {
+        minimap.toggle_shape();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) || rl.is_key_pressed(KeyboardKey::KEY_KP_ADD) {
+        minimap.adjust_zoom(ZOOM_STEP);
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_MINUS) || rl.is_key_pressed(KeyboardKey::KEY_KP_SUBTRACT)
+    {
+        minimap.adjust_zoom(1.0 / ZOOM_STEP);
+    }
+}
+
+pub fn process_map_events(
+    rl: &RaylibHandle,
+    overlay: &mut MapOverlay,
+    waypoints: &mut Waypoints,
+    maze: &[Vec<char>],
+    block_size: usize,
+) {
+    const PAN_SPEED: f32 = 8.0; // screen pixels per frame
+    const ZOOM_STEP: f32 = 1.25;
+
+    // Pan
+    if rl.is_key_down(KeyboardKey::KEY_LEFT) || rl.is_key_down(KeyboardKey::KEY_A) {
+        overlay.pan(-PAN_SPEED, 0.0);
+    }
+    if rl.is_key_down(KeyboardKey::KEY_RIGHT) || rl.is_key_down(KeyboardKey::KEY_D) {
+        overlay.pan(PAN_SPEED, 0.0);
+    }
+    if rl.is_key_down(KeyboardKey::KEY_UP) || rl.is_key_down(KeyboardKey::KEY_W) {
+        overlay.pan(0.0, -PAN_SPEED);
+    }
+    if rl.is_key_down(KeyboardKey::KEY_DOWN) || rl.is_key_down(KeyboardKey::KEY_S) {
+        overlay.pan(0.0, PAN_SPEED);
+    }
+
+    // Zoom
+    let wheel = rl.get_mouse_wheel_move();
+    if wheel != 0.0 {
+        overlay.adjust_zoom(ZOOM_STEP.powf(wheel));
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) || rl.is_key_pressed(KeyboardKey::KEY_KP_ADD) {
+        overlay.adjust_zoom(ZOOM_STEP);
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_MINUS) || rl.is_key_pressed(KeyboardKey::KEY_KP_SUBTRACT)
+    {
+        overlay.adjust_zoom(1.0 / ZOOM_STEP);
+    }
+
+    // Waypoints under the mouse cursor
+    let mouse = rl.get_mouse_position();
+    let (world_x, world_y) = overlay.transform().minimap_to_world(mouse.x, mouse.y);
+
+    if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+        && world_x >= 0.0
+        && world_y >= 0.0
+    {
+        let maze_x = (world_x as usize) / block_size;
+        let maze_y = (world_y as usize) / block_size;
+
+        // Snap to the centre of the clicked cell
+        if maze_y < maze.len() && maze_x < maze[maze_y].len() {
+            waypoints.place(
+                (maze_x * block_size + block_size / 2) as f32,
+                (maze_y * block_size + block_size / 2) as f32,
+            );
+        }
+    }
+    if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT) {
+        waypoints.remove_near(world_x, world_y, block_size as f32 / 2.0);
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+        waypoints.clear();
+    }
+}
+
+fn is_valid_position(
+    x: f32,
+    y: f32,
+    maze: &[Vec<char>],
+    materials: &MaterialRegistry,
+    doors: &Doors,
+    pushwalls: &Pushwalls,
+    block_size: usize,
+) -> bool {
     if x < 0.0 || y < 0.0 {
         return false;
     }
@@ -54,7 +185,67 @@ fn is_valid_position(x: f32, y: f32, maze: &Vec<Vec<char>>, block_size: usize) -
     }
 
     let cell = maze[maze_y][maze_x];
-    let is_valid = cell == ' ' || cell == 'p' || cell == 'g';
+    let is_valid = materials.is_walkable(cell)
+        && doors.is_passable(x, y)
+        && !pushwalls.is_blocked(x, y)
+        && !pushwalls.claims(maze_x, maze_y);
 
     is_valid
 }
+
+// Keys for the pause menu. Returns what the player chose, if anything.
+pub fn process_pause_events(rl: &RaylibHandle, menu: &mut PauseMenu) -> Option<PauseAction> {
+    // The P that opened the menu mustn't close it again straight away
+    let just_opened = menu.take_just_opened();
+    if rl.is_key_pressed(KeyboardKey::KEY_UP) || rl.is_key_pressed(KeyboardKey::KEY_W) {
+        menu.select(-1);
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_DOWN) || rl.is_key_pressed(KeyboardKey::KEY_S) {
+        menu.select(1);
+    }
+    let chosen =
+        rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.is_key_pressed(KeyboardKey::KEY_SPACE);
+
+    match menu.page {
+        PausePage::Main => {
+            if !just_opened && rl.is_key_pressed(KeyboardKey::KEY_P) {
+                return Some(PauseAction::Resume);
+            }
+            if !chosen {
+                return None;
+            }
+            match menu.selected {
+                0 => Some(PauseAction::Resume),
+                1 => Some(PauseAction::Restart),
+                2 => {
+                    menu.open_page(PausePage::Settings);
+                    None
+                }
+                _ => Some(PauseAction::QuitToMenu),
+            }
+        }
+        PausePage::Settings => {
+            let back = rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE);
+            if back || (chosen && menu.selected == 2) {
+                menu.open_page(PausePage::Main);
+                return None;
+            }
+            let steps = if rl.is_key_pressed(KeyboardKey::KEY_LEFT)
+                || rl.is_key_pressed(KeyboardKey::KEY_A)
+            {
+                -1.0
+            } else if rl.is_key_pressed(KeyboardKey::KEY_RIGHT)
+                || rl.is_key_pressed(KeyboardKey::KEY_D)
+            {
+                1.0
+            } else {
+                return None;
+            };
+            match menu.selected {
+                0 => Some(PauseAction::RenderScale(steps)),
+                1 => Some(PauseAction::Fov(steps)),
+                _ => None,
+            }
+        }
+    }
+}
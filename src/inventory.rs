@@ -0,0 +1,56 @@
+// src/inventory.rs
+use crate::materials::MaterialRegistry;
+use crate::player::Player;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+// Keys the player is carrying, and the cells they were taken from so their sprites go away
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    keys: BTreeSet<String>,
+    collected: HashSet<(usize, usize)>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self { keys: BTreeSet::new(), collected: HashSet::new() }
+    }
+
+    pub fn reset(&mut self) {
+        self.keys.clear();
+        self.collected.clear();
+    }
+
+    // Take the key from the player's cell, if there is one left. Returns its name.
+    pub fn pick_up<'a>(
+        &mut self,
+        player: &Player,
+        maze: &[Vec<char>],
+        materials: &'a MaterialRegistry,
+        block_size: usize,
+    ) -> Option<&'a str> {
+        let col = player.pos.x as usize / block_size;
+        let row = player.pos.y as usize / block_size;
+        let &cell = maze.get(row)?.get(col)?;
+        let key = materials.get(cell).key.as_ref()?;
+
+        if !self.collected.insert((col, row)) {
+            return None;
+        }
+        self.keys.insert(key.clone());
+        Some(key)
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    pub fn is_collected(&self, col: usize, row: usize) -> bool {
+        self.collected.contains(&(col, row))
+    }
+
+    // Held keys in name order, for the HUD
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(String::as_str)
+    }
+}
@@ -0,0 +1,38 @@
+// src/json_file.rs
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+#[error("failed to write {path}: {source}")]
+pub struct WriteError {
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+// Read an optional JSON file. Missing means defaults; a broken one is reported as
+// "invalid <what>" and ignored.
+pub fn load_or_default<T: DeserializeOwned + Default>(path: &Path, what: &str) -> T {
+    let Ok(text) = fs::read_to_string(path) else {
+        return T::default();
+    };
+
+    match serde_json::from_str(&text) {
+        Ok(value) => value,
+        Err(err) => {
+            println!("Warning: invalid {} {}: {}", what, path.display(), err);
+            T::default()
+        }
+    }
+}
+
+// Write pretty-printed JSON, creating the directory if needed
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), WriteError> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    serde_json::to_string_pretty(value)
+        .map_err(io::Error::other)
+        .and_then(|text| fs::create_dir_all(dir).and_then(|_| fs::write(path, text)))
+        .map_err(|source| WriteError { path: path.to_path_buf(), source })
+}
@@ -0,0 +1,25 @@
+// src/level.rs
+use crate::fog::Fog;
+use crate::json_file;
+use crate::lighting::LightingSettings;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+// Per-level options, read from a JSON file next to the maze (maze.txt -> maze.json).
+// Every field is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LevelSettings {
+    pub fog: Fog,
+    pub lighting: LightingSettings,
+}
+
+impl LevelSettings {
+    pub fn path_for(maze_path: &Path) -> PathBuf {
+        maze_path.with_extension("json")
+    }
+
+    pub fn load_for(maze_path: &Path) -> Self {
+        json_file::load_or_default(&Self::path_for(maze_path), "level settings")
+    }
+}
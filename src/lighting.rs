@@ -0,0 +1,240 @@
+// src/lighting.rs
+use crate::materials::MaterialRegistry;
+use raylib::prelude::*;
+use serde::Deserialize;
+
+// A light placed in a maze cell
+#[derive(Debug, Clone, Deserialize)]
+pub struct Light {
+    pub cell: [usize; 2], // [column, row]
+    #[serde(default = "white")]
+    pub color: [u8; 3],
+    pub radius: f32, // World units
+    #[serde(default = "one")]
+    pub intensity: f32,
+    #[serde(default)]
+    pub flicker: f32, // 0 steady, 1 may drop out completely
+}
+
+// Cone of light carried by the player
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Flashlight {
+    pub enabled: bool, // Switched on when the level starts
+    pub color: [u8; 3],
+    pub angle: f32, // Full cone width in degrees
+    pub range: f32,
+    pub intensity: f32,
+}
+
+impl Default for Flashlight {
+    fn default() -> Self {
+        Self { enabled: false, color: [255, 240, 200], angle: 30.0, range: 400.0, intensity: 1.2 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LightingSettings {
+    pub ambient: [u8; 3], // White keeps surfaces at their texture colour
+    pub lights: Vec<Light>,
+    pub flashlight: Flashlight,
+}
+
+impl Default for LightingSettings {
+    fn default() -> Self {
+        Self { ambient: white(), lights: Vec::new(), flashlight: Flashlight::default() }
+    }
+}
+
+fn white() -> [u8; 3] {
+    [255, 255, 255]
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+fn to_rgb(color: [u8; 3]) -> [f32; 3] {
+    color.map(|c| c as f32 / 255.0)
+}
+
+// Per-cell lightmap. What each light contributes to each cell is baked once, with walls
+// blocking it; only flicker is applied per frame.
+pub struct Lighting {
+    width: usize,
+    height: usize,
+    block_size: usize,
+    ambient: [f32; 3],
+    lights: Vec<Light>,
+    baked: Vec<Vec<(usize, [f32; 3])>>, // Per cell: (light index, contribution)
+    cells: Vec<[f32; 3]>,               // Current light per cell
+    flashlight: Flashlight,
+    pub flashlight_on: bool,
+}
+
+impl Lighting {
+    pub fn new(
+        settings: &LightingSettings,
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        block_size: usize,
+    ) -> Self {
+        let height = maze.len();
+        // Rows can be ragged; short ones are padded with open cells
+        let width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut baked = vec![Vec::new(); width * height];
+
+        for (index, light) in settings.lights.iter().enumerate() {
+            let [lx, ly] = light.cell;
+            if lx >= width || ly >= height {
+                println!("Warning: light at {:?} is outside the maze, skipping", light.cell);
+                continue;
+            }
+            let light_x = (lx as f32 + 0.5) * block_size as f32;
+            let light_y = (ly as f32 + 0.5) * block_size as f32;
+            let color = to_rgb(light.color);
+
+            for j in 0..height {
+                for i in 0..width {
+                    let cell_x = (i as f32 + 0.5) * block_size as f32;
+                    let cell_y = (j as f32 + 0.5) * block_size as f32;
+                    let distance = ((cell_x - light_x).powi(2) + (cell_y - light_y).powi(2)).sqrt();
+                    if distance >= light.radius {
+                        continue;
+                    }
+                    if !line_of_sight(maze, materials, block_size, (light_x, light_y), (i, j)) {
+                        continue;
+                    }
+
+                    let falloff = (1.0 - distance / light.radius).powi(2) * light.intensity;
+                    baked[j * width + i].push((index, color.map(|c| c * falloff)));
+                }
+            }
+        }
+
+        let mut lighting = Self {
+            width,
+            height,
+            block_size,
+            ambient: to_rgb(settings.ambient),
+            lights: settings.lights.clone(),
+            baked,
+            cells: vec![[0.0; 3]; width * height],
+            flashlight: settings.flashlight.clone(),
+            flashlight_on: settings.flashlight.enabled,
+        };
+        lighting.update(0.0);
+        lighting
+    }
+
+    pub fn reset(&mut self) {
+        self.flashlight_on = self.flashlight.enabled;
+        self.update(0.0);
+    }
+
+    pub fn toggle_flashlight(&mut self) {
+        self.flashlight_on = !self.flashlight_on;
+    }
+
+    // Recompute every cell for this frame's flicker
+    pub fn update(&mut self, time: f32) {
+        let scales: Vec<f32> = self
+            .lights
+            .iter()
+            .enumerate()
+            .map(|(index, light)| flicker_scale(light.flicker, time, index))
+            .collect();
+
+        for (cell, contributions) in self.cells.iter_mut().zip(&self.baked) {
+            *cell = self.ambient;
+            for (index, color) in contributions {
+                for channel in 0..3 {
+                    cell[channel] += color[channel] * scales[*index];
+                }
+            }
+        }
+    }
+
+    // Light reaching a world position from the lightmap
+    pub fn light_at(&self, x: f32, y: f32) -> [f32; 3] {
+        if x < 0.0 || y < 0.0 {
+            return self.ambient;
+        }
+        let i = x as usize / self.block_size;
+        let j = y as usize / self.block_size;
+        if i >= self.width || j >= self.height {
+            return self.ambient;
+        }
+        self.cells[j * self.width + i]
+    }
+
+    // Flashlight contribution for a surface `distance` away, `angle` radians off the view centre
+    pub fn flashlight_at(&self, distance: f32, angle: f32) -> [f32; 3] {
+        if !self.flashlight_on {
+            return [0.0; 3];
+        }
+
+        let half_cone = self.flashlight.angle.to_radians() / 2.0;
+        let cone = (1.0 - angle.abs() / half_cone).clamp(0.0, 1.0);
+        let reach = (1.0 - distance / self.flashlight.range).clamp(0.0, 1.0);
+        // Soften the edge of the beam
+        let strength = cone * (2.0 - cone) * reach * reach * self.flashlight.intensity;
+
+        to_rgb(self.flashlight.color).map(|c| c * strength)
+    }
+
+    // Total light on a surface: lightmap at its position plus the flashlight
+    pub fn light_surface(&self, x: f32, y: f32, distance: f32, angle: f32) -> [f32; 3] {
+        let map = self.light_at(x, y);
+        let torch = self.flashlight_at(distance, angle);
+        [map[0] + torch[0], map[1] + torch[1], map[2] + torch[2]]
+    }
+}
+
+pub fn apply_light(color: Color, light: [f32; 3]) -> Color {
+    let lit = |channel: u8, amount: f32| (channel as f32 * amount).min(255.0) as u8;
+    Color::new(lit(color.r, light[0]), lit(color.g, light[1]), lit(color.b, light[2]), color.a)
+}
+
+// Walks from the light towards the cell centre, stopping at the first cell that blocks sight.
+// The light's own cell never blocks, so lights can sit inside a wall.
+fn line_of_sight(
+    maze: &[Vec<char>],
+    materials: &MaterialRegistry,
+    block_size: usize,
+    from: (f32, f32),
+    cell: (usize, usize),
+) -> bool {
+    let to_x = (cell.0 as f32 + 0.5) * block_size as f32;
+    let to_y = (cell.1 as f32 + 0.5) * block_size as f32;
+    let distance = ((to_x - from.0).powi(2) + (to_y - from.1).powi(2)).sqrt();
+    let steps = (distance / (block_size as f32 / 4.0)).ceil() as usize;
+    let start = (from.0 as usize / block_size, from.1 as usize / block_size);
+
+    for step in 0..steps {
+        let t = step as f32 / steps as f32;
+        let i = (from.0 + (to_x - from.0) * t) as usize / block_size;
+        let j = (from.1 + (to_y - from.1) * t) as usize / block_size;
+        if (i, j) == cell {
+            return true;
+        }
+        // Cells past the end of a short row are open
+        let blocks =
+            maze.get(j).and_then(|row| row.get(i)).is_some_and(|&c| materials.blocks_sight(c));
+        if (i, j) != start && blocks {
+            return false;
+        }
+    }
+    true
+}
+
+// Brightness multiplier for a flickering light, deterministic in time
+fn flicker_scale(flicker: f32, time: f32, index: usize) -> f32 {
+    if flicker <= 0.0 {
+        return 1.0;
+    }
+    let phase = index as f32 * 1.7;
+    let wobble = 0.5 + 0.3 * (time * 11.0 + phase).sin() + 0.2 * (time * 23.0 + phase * 2.3).sin();
+    1.0 - flicker.min(1.0) * wobble.clamp(0.0, 1.0)
+}
@@ -1,26 +1,88 @@
 // src/main.rs
+mod assets;
+mod benchmark;
 mod caster;
+mod checkpoints;
+mod doors;
+mod exploration;
+mod flash;
+mod fog;
 mod framebuffer;
 mod game_state;
 mod input;
+mod inventory;
+mod json_file;
+mod level;
+mod lighting;
+mod map_overlay;
+mod materials;
 mod maze;
 mod minimap;
+mod pathfinding;
+mod pause_menu;
 mod player;
+mod procedural;
+mod pushwalls;
+mod records;
 mod render3d;
 mod resources;
-
+mod save;
+mod settings;
+mod teleporters;
+mod validator;
+mod waypoints;
+
+use anyhow::Context;
+use assets::{AssetError, AssetRoot};
+use checkpoints::{format_delta, format_time};
+use flash::ScreenFlash;
 use framebuffer::Framebuffer;
 use game_state::{GameManager, GameState};
-use input::process_events;
+use input::{process_events, process_map_events, process_minimap_events, process_pause_events};
+use level::LevelSettings;
+use lighting::Lighting;
+use map_overlay::MapOverlay;
+use materials::MaterialRegistry;
 use maze::load_maze;
 use minimap::Minimap;
+use pause_menu::{PauseAction, PauseMenu};
 use player::Player;
 use raylib::prelude::*;
-use render3d::render3d;
+use records::{Record, Records, level_key, today};
+use render3d::{Renderer3d, World, render_sprites, render_waypoints};
 use resources::Textures;
+use save::SaveGame;
+use settings::Settings;
+use std::path::Path;
+use waypoints::WAYPOINT_COLOR;
+
+fn main() -> anyhow::Result<()> {
+    let assets = AssetRoot::locate();
+    let maze_path = assets.resolve("maze.txt");
+    // Pushwalls move cells around during play; `level_maze` is kept to restore them
+    let level_maze = load_maze(&maze_path)?;
+    let mut maze = level_maze.clone();
+    let level_settings = LevelSettings::load_for(&maze_path);
+
+    // Fall back to the compiled-in manifest so a missing file doesn't stop the game
+    let materials_path = assets.resolve("materials.json");
+    let materials = match MaterialRegistry::load(&materials_path) {
+        Ok(materials) => materials,
+        Err(source) => {
+            let err = AssetError::Materials { path: materials_path, source };
+            println!("Warning: {}, using built-in materials", err);
+            MaterialRegistry::builtin()
+        }
+    };
+
+    // Problems are reported but don't stop the level loading
+    for problem in validator::validate(&maze, &materials) {
+        println!("Warning: {}: {}", maze_path.display(), problem);
+    }
 
-fn main() {
-    let maze = load_maze("./maze.txt");
+    // Preferences are written by the game, so they live with the user, not the assets
+    let settings_path = assets::user_data_dir().map(|dir| dir.join(Settings::FILE_NAME));
+    let mut settings = settings_path.as_deref().map(Settings::load).unwrap_or_default();
 
     let block_size = 64;
     let screen_width = 1024;
@@ -34,34 +96,116 @@ fn main() {
 
     let mut framebuffer = Framebuffer::new(screen_width as u32, screen_height as u32, Color::BLACK);
 
+    // The 3D view renders at the configured scale and is stretched over the window
+    let (scene_width, scene_height) = settings.render_size(framebuffer.width, framebuffer.height);
+    let mut scene = Framebuffer::new(scene_width, scene_height, Color::BLACK);
+
     let mut player =
-        Player::from_maze(&maze, block_size).expect("No se encontró 'p' en el laberinto");
+        Player::from_maze(&maze, block_size).context("No se encontró 'p' en el laberinto")?;
+
+    player.fov = settings.fov();
 
     let initial_player_pos = player.pos;
     let initial_player_angle = player.a;
 
-    let mut game_manager = GameManager::new();
+    let mut game_manager = GameManager::new(&maze, &materials, &player, block_size);
+    let mut lighting = Lighting::new(&level_settings.lighting, &maze, &materials, block_size);
+    let mut flash = ScreenFlash::new();
+
+    // Best times live in the user's data directory, one board per maze layout
+    let records_path = assets::user_data_dir().map(|dir| dir.join(Records::FILE_NAME));
+    let mut records = records_path.as_deref().map(Records::load).unwrap_or_default();
+    let level = level_key(&level_maze);
+    if let Some(best) = records.top(&level).first() {
+        game_manager.checkpoints.set_best(best.splits.clone());
+    }
+    let mut record_place = None; // Where the last finished run landed on the board
+
+    // The run in progress is saved at checkpoints and on quit, and continued from the menu
+    let save_path = assets::user_data_dir().map(|dir| dir.join(SaveGame::FILE_NAME));
+    let mut has_save = save_path.as_deref().is_some_and(Path::is_file);
+
+    let mut pause_menu = PauseMenu::new();
 
     // Create minimap
-    let minimap = Minimap::new(screen_width as u32, screen_height as u32);
+    let mut minimap = Minimap::new(&maze, block_size);
+
+    // Full-screen map, toggled during play
+    let mut map_overlay = MapOverlay::new(screen_width as u32, screen_height as u32, block_size);
 
     // Load textures with raylib handle
-    let textures = Textures::new(&mut rl, &thread);
+    let textures = Textures::new(&mut rl, &thread, &materials, &assets);
+
+    if std::env::args().any(|arg| arg == "--benchmark") {
+        let world = World {
+            player: &player,
+            maze: &maze,
+            materials: &materials,
+            textures: &textures,
+            lighting: &lighting,
+            fog: &level_settings.fog,
+            doors: &game_manager.doors,
+            inventory: &game_manager.inventory,
+            pushwalls: &game_manager.pushwalls,
+            time: 0.0,
+        };
+        benchmark::run(&world, settings.render_threads());
+        return Ok(());
+    }
+
+    // The thread count is looked up once; asking the OS every frame isn't free
+    let mut renderer = Renderer3d::new(settings.render_threads());
 
     // Set target FPS
     rl.set_target_fps(60);
 
+    // ESC means "back to the menu" on the death and victory screens, so it only quits from
+    // the menu and during play
+    rl.set_exit_key(None);
+
     while !rl.window_should_close() {
         let delta_time = rl.get_frame_time();
         let fps = rl.get_fps();
 
+        if matches!(game_manager.state, GameState::Menu | GameState::Playing)
+            && rl.is_key_pressed(KeyboardKey::KEY_ESCAPE)
+        {
+            break;
+        }
+
+        // P or switching to another window pauses; the timer only runs while Playing
+        if game_manager.state == GameState::Playing
+            && (rl.is_key_pressed(KeyboardKey::KEY_P) || !rl.is_window_focused())
+        {
+            game_manager.state = GameState::Paused;
+            pause_menu.open();
+        }
+
         match game_manager.state {
             GameState::Menu => {
                 if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-                    game_manager.reset();
-                    // Reset player to initial position
-                    player.pos = initial_player_pos;
-                    player.a = initial_player_angle;
+                    restart(
+                        &mut game_manager,
+                        &mut player,
+                        &mut maze,
+                        &level_maze,
+                        &mut lighting,
+                        &mut flash,
+                    );
+                } else if has_save && rl.is_key_pressed(KeyboardKey::KEY_C) {
+                    match save_path.as_deref().map(|path| SaveGame::load(path, &level, &level_maze))
+                    {
+                        Some(Ok(save)) => {
+                            save.apply(&mut game_manager, &mut player, &mut maze);
+                            lighting.reset();
+                            flash.reset();
+                        }
+                        Some(Err(err)) => {
+                            println!("Warning: {}", err);
+                            has_save = false;
+                        }
+                        None => {}
+                    }
                 }
 
                 let mut d = rl.begin_drawing(&thread);
@@ -84,6 +228,15 @@ fn main() {
                     24,
                     Color::GRAY,
                 );
+                if has_save {
+                    d.draw_text(
+                        "Press C to Continue",
+                        screen_width / 2 - 105,
+                        screen_height / 2 + 25,
+                        20,
+                        Color::LIGHTGRAY,
+                    );
+                }
                 d.draw_text(
                     "Controls:",
                     screen_width / 2 - 50,
@@ -105,36 +258,259 @@ fn main() {
                     18,
                     Color::GREEN,
                 );
+                let minimap_text = "Maps show where you've been - V reveals the whole maze";
                 d.draw_text(
-                    "Minimap shows your position",
-                    screen_width / 2 - 125,
+                    minimap_text,
+                    screen_width / 2 - measure_text(minimap_text, 18) / 2,
                     screen_height / 2 + 140,
                     18,
                     Color::YELLOW,
                 );
+                d.draw_text(
+                    "TAB - Minimap Mode   +/- - Zoom   Q - Full Map   L - Flashlight   E - Use",
+                    screen_width / 2 - 250,
+                    screen_height / 2 + 170,
+                    18,
+                    Color::DARKGRAY,
+                );
+                d.draw_text(
+                    "[ / ] - Render Scale   , / . - FOV   PgUp/PgDn - Look   P - Pause",
+                    screen_width / 2 - 250,
+                    screen_height / 2 + 195,
+                    18,
+                    Color::DARKGRAY,
+                );
             }
 
             GameState::Playing => {
-                process_events(&rl, &mut player, &maze, block_size);
+                // Toggle the full-screen map
+                if rl.is_key_pressed(KeyboardKey::KEY_Q) {
+                    if map_overlay.is_open {
+                        map_overlay.close();
+                    } else {
+                        map_overlay.open(&player, &maze);
+                    }
+                }
 
-                // Check victory condition
-                if player.check_victory(&maze, block_size) {
-                    game_manager.state = GameState::Victory;
+                if rl.is_key_pressed(KeyboardKey::KEY_L) {
+                    lighting.toggle_flashlight();
                 }
 
-                game_manager.level_time += delta_time;
+                // V reveals the whole maze, goal included, on both maps; off again restores
+                // what has been explored
+                if rl.is_key_pressed(KeyboardKey::KEY_V) {
+                    minimap.fog_of_war = !minimap.fog_of_war;
+                    map_overlay.fog_of_war = minimap.fog_of_war;
+                }
 
-                // Render 3D view
-                framebuffer.clear();
-                render3d(&mut framebuffer, &player, &maze, &textures);
+                // Render scale: [ lowers it for speed, ] raises it for detail
+                let scale_steps = if rl.is_key_pressed(KeyboardKey::KEY_LEFT_BRACKET) {
+                    -1.0
+                } else if rl.is_key_pressed(KeyboardKey::KEY_RIGHT_BRACKET) {
+                    1.0
+                } else {
+                    0.0
+                };
+                // Field of view: , narrows it and . widens it
+                let fov_steps = if rl.is_key_pressed(KeyboardKey::KEY_COMMA) {
+                    -1.0
+                } else if rl.is_key_pressed(KeyboardKey::KEY_PERIOD) {
+                    1.0
+                } else {
+                    0.0
+                };
+                change_settings(
+                    &mut settings,
+                    settings_path.as_deref(),
+                    (scale_steps, fov_steps),
+                    &mut scene,
+                    &framebuffer,
+                    &mut player,
+                );
+
+                if map_overlay.is_open {
+                    process_map_events(
+                        &rl,
+                        &mut map_overlay,
+                        &mut game_manager.waypoints,
+                        &maze,
+                        block_size,
+                    );
+                } else {
+                    process_events(
+                        &rl,
+                        &mut player,
+                        &maze,
+                        &materials,
+                        &game_manager.doors,
+                        &game_manager.pushwalls,
+                        block_size,
+                    );
+                    game_manager.teleporters.update(&mut player, &maze, &materials, block_size);
+                    player.follow_floor(&maze, &materials, block_size);
+
+                    // E pushes a secret wall or uses the door in front; locked doors say
+                    // which key they need
+                    if rl.is_key_pressed(KeyboardKey::KEY_E) {
+                        if game_manager.pushwalls.push(&player, &mut maze, &materials) {
+                            game_manager.find_secret();
+                        } else if let Some(key) = game_manager
+                            .doors
+                            .use_door(&player, &maze, &materials, &game_manager.inventory)
+                            .map(str::to_string)
+                        {
+                            game_manager.notify(format!("This door needs the {} key", key));
+                        }
+                    }
+                    if let Some(key) =
+                        game_manager.inventory.pick_up(&player, &maze, &materials, block_size)
+                    {
+                        let text = format!("Picked up the {} key", key);
+                        game_manager.notify(text);
+                    }
+                    if game_manager.update_checkpoints(&player, block_size) {
+                        let save = SaveGame::capture(&level, &game_manager, &player, &maze);
+                        has_save |= save_game(save_path.as_deref(), &save);
+                    }
+                    process_minimap_events(&rl, &mut minimap, &maze);
+
+                    // Check victory condition
+                    if player.check_victory(&maze, block_size) {
+                        game_manager.finish();
+                        let record = Record {
+                            name: settings.player_name.clone(),
+                            time: game_manager.level_time,
+                            date: today(),
+                            splits: game_manager.checkpoints.splits().to_vec(),
+                        };
+                        record_place = records.add(&level, record);
+                        // A finished run has nothing to continue
+                        if let Some(path) = &save_path
+                            && let Err(err) = SaveGame::delete(path)
+                        {
+                            println!("Warning: {}", err);
+                        }
+                        has_save = false;
+                        if let Some(path) = &records_path
+                            && let Err(err) = records.save(path)
+                        {
+                            println!("Warning: {}", err);
+                        }
+                    }
+                }
+
+                // The clock stops on the frame the goal is reached, so the victory screen
+                // shows the time that was recorded
+                if game_manager.state == GameState::Playing {
+                    game_manager.level_time += delta_time;
+                    game_manager.doors.update(delta_time, &player, &game_manager.inventory);
+                    game_manager.pushwalls.update(delta_time, &mut maze);
+                    lighting.update(game_manager.level_time);
+
+                    // Hazards hurt for as long as the player stands in them, with a red flash
+                    flash.update(delta_time);
+                    if player.take_hazard_damage(&maze, &materials, block_size, delta_time) > 0.0 {
+                        flash.trigger(Color::RED, 0.35);
+                    }
+                    if player.is_dead() {
+                        game_manager.state = GameState::Dead;
+                        flash.trigger(Color::RED, 0.6);
+                    }
+                }
 
-                // Render minimap on top of 3D view
-                minimap.render(&mut framebuffer, &player, &maze, block_size);
+                framebuffer.clear();
+                let mut waypoint_labels = Vec::new();
+
+                if map_overlay.is_open {
+                    map_overlay.render(
+                        &mut framebuffer,
+                        &player,
+                        &maze,
+                        &materials,
+                        &game_manager.exploration,
+                        &game_manager.waypoints,
+                    );
+                } else {
+                    // Render 3D view
+                    let world = World {
+                        player: &player,
+                        maze: &maze,
+                        materials: &materials,
+                        textures: &textures,
+                        lighting: &lighting,
+                        fog: &level_settings.fog,
+                        doors: &game_manager.doors,
+                        inventory: &game_manager.inventory,
+                        pushwalls: &game_manager.pushwalls,
+                        time: game_manager.level_time,
+                    };
+                    scene.clear();
+                    renderer.render(&mut scene, &world, &mut game_manager.exploration);
+                    render_sprites(&mut scene, &world);
+                    let labels = render_waypoints(&mut scene, &world, &game_manager.waypoints);
+                    framebuffer.blit_scaled(&scene);
+
+                    // Labels come back in scene pixels
+                    let scale_x = framebuffer.width as f32 / scene.width as f32;
+                    let scale_y = framebuffer.height as f32 / scene.height as f32;
+                    waypoint_labels = labels
+                        .into_iter()
+                        .map(|(number, x, y)| {
+                            (number, (x as f32 * scale_x) as i32, (y as f32 * scale_y) as i32)
+                        })
+                        .collect();
+
+                    // Render minimap on top of 3D view
+                    minimap.render(
+                        &mut framebuffer,
+                        &player,
+                        &maze,
+                        &materials,
+                        &game_manager.exploration,
+                        &game_manager.waypoints,
+                    );
+                    flash.apply(&mut framebuffer);
+                }
 
                 let mut d = rl.begin_drawing(&thread);
                 d.clear_background(Color::BLACK);
                 framebuffer.draw_to_screen(&mut d);
 
+                if map_overlay.is_open {
+                    map_overlay.draw_labels(&mut d, &game_manager.waypoints);
+                } else {
+                    // Waypoint numbers above their 3D markers
+                    for (number, x, y) in waypoint_labels {
+                        d.draw_text(&number.to_string(), x - 5, y, 20, WAYPOINT_COLOR);
+                    }
+
+                    // Minimap label
+                    d.draw_text("MINIMAP", 30, screen_height - 30, 16, Color::WHITE);
+
+                    // Exploration progress
+                    let explored_text =
+                        format!("Explored: {:.0}%", game_manager.exploration.percent_explored());
+                    d.draw_text(&explored_text, 110, screen_height - 30, 16, Color::LIGHTGRAY);
+
+                    // Health bar, bottom centre; turns red when low
+                    let bar_width = 200;
+                    let bar_x = screen_width / 2 - bar_width / 2;
+                    let bar_y = screen_height - 30;
+                    let health = player.health / Player::MAX_HEALTH;
+                    let bar_color = if health < 0.3 { Color::RED } else { Color::LIME };
+                    d.draw_rectangle(bar_x, bar_y, bar_width, 16, Color::new(60, 0, 0, 200));
+                    d.draw_rectangle(
+                        bar_x,
+                        bar_y,
+                        (bar_width as f32 * health) as i32,
+                        16,
+                        bar_color,
+                    );
+                    d.draw_rectangle_lines(bar_x, bar_y, bar_width, 16, Color::WHITE);
+                    let health_text = format!("HP {:.0}", player.health.ceil());
+                    d.draw_text(&health_text, bar_x + bar_width + 8, bar_y, 16, Color::WHITE);
+                }
+
                 // Draw FPS counter
                 let fps_text = format!("FPS: {}", fps);
                 let text_width = measure_text(&fps_text, 20);
@@ -145,22 +521,164 @@ fn main() {
                     d.draw_text("LOW FPS!", screen_width - 80, 35, 16, Color::RED);
                 }
 
-                // Minimap label
-                d.draw_text("MINIMAP", 30, screen_height - 30, 16, Color::WHITE);
+                let scale_text = format!(
+                    "Render: {:.0}%  FOV: {:.0}",
+                    settings.render_scale * 100.0,
+                    settings.fov_degrees
+                );
+                let text_width = measure_text(&scale_text, 16);
+                d.draw_text(&scale_text, screen_width - text_width - 10, 55, 16, Color::GRAY);
+
+                // Run timer, with how the last split compares to the best run
+                let timer_text = format_time(game_manager.level_time);
+                let text_width = measure_text(&timer_text, 24);
+                d.draw_text(&timer_text, screen_width / 2 - text_width / 2, 10, 24, Color::WHITE);
+                if let Some(delta) = game_manager.checkpoints.delta() {
+                    let delta_text = format_delta(delta);
+                    let color = if delta < 0.0 { Color::LIME } else { Color::RED };
+                    let text_width = measure_text(&delta_text, 18);
+                    d.draw_text(&delta_text, screen_width / 2 - text_width / 2, 38, 18, color);
+                }
+
+                // Checkpoint count and held keys stack down the right side
+                let mut column_y = 80;
+                if game_manager.checkpoints.total() > 0 {
+                    let checkpoint_text = format!(
+                        "Checkpoints: {}/{}",
+                        game_manager.checkpoints.reached(),
+                        game_manager.checkpoints.total()
+                    );
+                    let text_width = measure_text(&checkpoint_text, 16);
+                    d.draw_text(
+                        &checkpoint_text,
+                        screen_width - text_width - 10,
+                        column_y,
+                        16,
+                        Color::GREEN,
+                    );
+                    column_y += 20;
+                }
+
+                // Held keys, one coloured tag each
+                for key in game_manager.inventory.keys() {
+                    let color = materials.key_color(key);
+                    let label = format!("{} key", key);
+                    let text_width = measure_text(&label, 16);
+                    d.draw_rectangle(screen_width - text_width - 30, column_y + 3, 12, 12, color);
+                    d.draw_text(&label, screen_width - text_width - 10, column_y, 16, color);
+                    column_y += 20;
+                }
+
+                if let Some(message) = game_manager.message() {
+                    let text_width = measure_text(message, 20);
+                    d.draw_text(
+                        message,
+                        screen_width / 2 - text_width / 2,
+                        screen_height / 2 + 60,
+                        20,
+                        Color::WHITE,
+                    );
+                }
+            }
+
+            GameState::Paused => {
+                match process_pause_events(&rl, &mut pause_menu) {
+                    Some(PauseAction::Resume) => game_manager.state = GameState::Playing,
+                    Some(PauseAction::Restart) => restart(
+                        &mut game_manager,
+                        &mut player,
+                        &mut maze,
+                        &level_maze,
+                        &mut lighting,
+                        &mut flash,
+                    ),
+                    Some(PauseAction::QuitToMenu) => {
+                        // Keep the run for Continue
+                        let save = SaveGame::capture(&level, &game_manager, &player, &maze);
+                        has_save |= save_game(save_path.as_deref(), &save);
+                        game_manager.state = GameState::Menu;
+                    }
+                    Some(PauseAction::RenderScale(steps)) => change_settings(
+                        &mut settings,
+                        settings_path.as_deref(),
+                        (steps, 0.0),
+                        &mut scene,
+                        &framebuffer,
+                        &mut player,
+                    ),
+                    Some(PauseAction::Fov(steps)) => change_settings(
+                        &mut settings,
+                        settings_path.as_deref(),
+                        (0.0, steps),
+                        &mut scene,
+                        &framebuffer,
+                        &mut player,
+                    ),
+                    None => {}
+                }
+
+                // The frozen frame stays up behind the menu
+                let mut d = rl.begin_drawing(&thread);
+                d.clear_background(Color::BLACK);
+                framebuffer.draw_to_screen(&mut d);
+                pause_menu.draw(&mut d, &settings, screen_width, screen_height);
+            }
+
+            GameState::Dead => {
+                if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+                    game_manager.respawn(&mut player);
+                    flash.reset();
+                } else if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                    game_manager.state = GameState::Menu;
+                }
+
+                // The last frame stays up, darkened, behind the message
+                let mut d = rl.begin_drawing(&thread);
+                d.clear_background(Color::BLACK);
+                framebuffer.draw_to_screen(&mut d);
+                d.draw_rectangle(0, 0, screen_width, screen_height, Color::new(60, 0, 0, 160));
+
+                d.draw_text(
+                    "YOU DIED",
+                    screen_width / 2 - 110,
+                    screen_height / 2 - 80,
+                    45,
+                    Color::RED,
+                );
+                d.draw_text(
+                    "Press SPACE to Respawn at the Last Checkpoint",
+                    screen_width / 2 - 240,
+                    screen_height / 2,
+                    22,
+                    Color::LIGHTGRAY,
+                );
+                d.draw_text(
+                    "Press ESC to Return to Menu",
+                    screen_width / 2 - 140,
+                    screen_height / 2 + 30,
+                    22,
+                    Color::LIGHTGRAY,
+                );
             }
 
             GameState::Victory => {
                 // Handle input for restart
                 if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
                     // Restart the game
-                    game_manager.reset();
-                    player.pos = initial_player_pos;
-                    player.a = initial_player_angle;
+                    restart(
+                        &mut game_manager,
+                        &mut player,
+                        &mut maze,
+                        &level_maze,
+                        &mut lighting,
+                        &mut flash,
+                    );
                 } else if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
                     // Return to menu (but don't use KEY_M here since it's used for mute)
                     game_manager.state = GameState::Menu;
                     player.pos = initial_player_pos;
                     player.a = initial_player_angle;
+                    player.pitch = 0.0;
                 }
 
                 let mut d = rl.begin_drawing(&thread);
@@ -186,6 +704,16 @@ fn main() {
                     Color::GREEN,
                 );
 
+                if record_place == Some(0) {
+                    d.draw_text(
+                        "New record!",
+                        screen_width / 2 - 70,
+                        screen_height / 2 - 38,
+                        24,
+                        Color::GOLD,
+                    );
+                }
+
                 // Show completion time
                 d.draw_text(
                     &format!("Completion Time: {:.2} seconds", game_manager.level_time),
@@ -194,8 +722,34 @@ fn main() {
                     24,
                     Color::WHITE,
                 );
+                if let Some(delta) = game_manager.checkpoints.delta() {
+                    let color = if delta < 0.0 { Color::LIME } else { Color::RED };
+                    d.draw_text(
+                        &format!("({} on best)", format_delta(delta)),
+                        screen_width / 2 + 230,
+                        screen_height / 2 - 10,
+                        24,
+                        color,
+                    );
+                }
+
+                let stats = game_manager.stats;
+                let mut summary = format!(
+                    "Explored: {:.0}% of the maze",
+                    game_manager.exploration.percent_explored()
+                );
+                if stats.secrets_total > 0 {
+                    summary +=
+                        &format!("   Secrets: {}/{}", stats.secrets_found, stats.secrets_total);
+                }
+                d.draw_text(
+                    &summary,
+                    screen_width / 2 - 150,
+                    screen_height / 2 + 15,
+                    20,
+                    Color::LIGHTGRAY,
+                );
 
-                // Show best time (optional - would need to track this)
                 d.draw_rectangle(
                     screen_width / 2 - 200,
                     screen_height / 2 + 40,
@@ -204,31 +758,43 @@ fn main() {
                     Color::WHITE,
                 );
 
-                // Restart instructions
+                // Local best times for this maze, with the run just finished highlighted
                 d.draw_text(
-                    "Press SPACE to Play Again",
-                    screen_width / 2 - 140,
-                    screen_height / 2 + 60,
-                    22,
-                    Color::LIGHTGRAY,
+                    "BEST TIMES",
+                    screen_width / 2 - 55,
+                    screen_height / 2 + 48,
+                    18,
+                    Color::GOLD,
                 );
+                const BOARD_ROWS: usize = 5;
+                for (place, record) in records.top(&level).iter().take(BOARD_ROWS).enumerate() {
+                    let line = format!(
+                        "{}. {:<12} {}   {}",
+                        place + 1,
+                        record.name,
+                        format_time(record.time),
+                        record.date
+                    );
+                    let color =
+                        if record_place == Some(place) { Color::GOLD } else { Color::LIGHTGRAY };
+                    d.draw_text(
+                        &line,
+                        screen_width / 2 - 150,
+                        screen_height / 2 + 70 + place as i32 * 18,
+                        16,
+                        color,
+                    );
+                }
 
+                // Restart instructions
                 d.draw_text(
-                    "Press ESC to Return to Menu",
-                    screen_width / 2 - 140,
-                    screen_height / 2 + 90,
+                    "SPACE - Play Again   ESC - Menu",
+                    screen_width / 2 - 170,
+                    screen_height / 2 + 170,
                     22,
                     Color::LIGHTGRAY,
                 );
 
-                d.draw_text(
-                    "Press ESC to Quit",
-                    screen_width / 2 - 100,
-                    screen_height / 2 + 120,
-                    22,
-                    Color::DARKGRAY,
-                );
-
                 // Still show FPS counter
                 let fps_text = format!("FPS: {}", fps);
                 let text_width = measure_text(&fps_text, 20);
@@ -239,6 +805,71 @@ fn main() {
             }
         }
     }
+
+    // Quitting mid-run keeps it for Continue
+    if matches!(game_manager.state, GameState::Playing | GameState::Paused | GameState::Dead) {
+        let save = SaveGame::capture(&level, &game_manager, &player, &maze);
+        save_game(save_path.as_deref(), &save);
+    }
+
+    Ok(())
+}
+
+// Start the level over: the run, the maze, the lights and the player
+fn restart(
+    game_manager: &mut GameManager,
+    player: &mut Player,
+    maze: &mut Vec<Vec<char>>,
+    level_maze: &Vec<Vec<char>>,
+    lighting: &mut Lighting,
+    flash: &mut ScreenFlash,
+) {
+    game_manager.reset();
+    // After a reset the respawn point is the level start
+    game_manager.respawn(player);
+    maze.clone_from(level_maze);
+    lighting.reset();
+    flash.reset();
+}
+
+// Change the render scale and field of view by some steps each, and keep them for next time
+fn change_settings(
+    settings: &mut Settings,
+    settings_path: Option<&Path>,
+    (scale_steps, fov_steps): (f32, f32),
+    scene: &mut Framebuffer,
+    screen: &Framebuffer,
+    player: &mut Player,
+) {
+    if scale_steps != 0.0 {
+        settings.adjust_render_scale(scale_steps);
+        let (width, height) = settings.render_size(screen.width, screen.height);
+        *scene = Framebuffer::new(width, height, Color::BLACK);
+    }
+    if fov_steps != 0.0 {
+        settings.adjust_fov(fov_steps);
+        player.fov = settings.fov();
+    }
+    if (scale_steps != 0.0 || fov_steps != 0.0)
+        && let Some(path) = settings_path
+        && let Err(err) = settings.save(path)
+    {
+        println!("Warning: {}", err);
+    }
+}
+
+// Write the save file, if there is somewhere to put it. Returns whether it was written.
+fn save_game(path: Option<&Path>, save: &SaveGame) -> bool {
+    let Some(path) = path else {
+        return false;
+    };
+    match save.save(path) {
+        Ok(()) => true,
+        Err(err) => {
+            println!("Warning: {}", err);
+            false
+        }
+    }
 }
 
 // Helper function to measure text width
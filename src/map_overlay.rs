@@ -0,0 +1,160 @@
+// src/map_overlay.rs
+use crate::exploration::ExplorationMap;
+use crate::framebuffer::{ClipRegion, Framebuffer};
+use crate::materials::MaterialRegistry;
+use crate::minimap::{MinimapTransform, draw_maze_cells};
+use crate::player::Player;
+use crate::waypoints::{WAYPOINT_COLOR, Waypoints};
+use raylib::prelude::*;
+
+// Full-screen map view, separate from the corner minimap. Uses the same colour legend.
+pub struct MapOverlay {
+    pub is_open: bool,
+    pub zoom: f32,     // screen pixels per maze cell
+    pub center_x: f32, // world point shown at the middle of the screen
+    pub center_y: f32,
+    pub fog_of_war: bool,
+    pub block_size: usize,
+    screen_width: u32,
+    screen_height: u32,
+}
+
+impl MapOverlay {
+    pub const MIN_ZOOM: f32 = 4.0;
+    pub const MAX_ZOOM: f32 = 96.0;
+
+    pub fn new(screen_width: u32, screen_height: u32, block_size: usize) -> Self {
+        Self {
+            is_open: false,
+            zoom: 32.0,
+            center_x: 0.0,
+            center_y: 0.0,
+            fog_of_war: true,
+            block_size,
+            screen_width,
+            screen_height,
+        }
+    }
+
+    // Open centred on the player, zoomed so the whole maze fits
+    pub fn open(&mut self, player: &Player, maze: &[Vec<char>]) {
+        let cols = maze.iter().map(|row| row.len()).max().unwrap_or(1).max(1);
+        let rows = maze.len().max(1);
+        let fit_zoom = (self.screen_width as f32 / cols as f32)
+            .min(self.screen_height as f32 / rows as f32)
+            * 0.9;
+
+        self.is_open = true;
+        self.zoom = fit_zoom.clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        self.center_x = player.pos.x;
+        self.center_y = player.pos.y;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+    }
+
+    // Pan by a distance in screen pixels
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let scale = self.block_size as f32 / self.zoom;
+        self.center_x += dx * scale;
+        self.center_y += dy * scale;
+    }
+
+    pub fn adjust_zoom(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+    }
+
+    pub fn transform(&self) -> MinimapTransform {
+        MinimapTransform {
+            scale: self.block_size as f32 / self.zoom,
+            rotation: 0.0,
+            world_x: self.center_x,
+            world_y: self.center_y,
+            screen_x: self.screen_width as f32 / 2.0,
+            screen_y: self.screen_height as f32 / 2.0,
+        }
+    }
+
+    pub fn render(
+        &self,
+        framebuffer: &mut Framebuffer,
+        player: &Player,
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        exploration: &ExplorationMap,
+        waypoints: &Waypoints,
+    ) {
+        let transform = self.transform();
+        let viewport = ClipRegion::Rect {
+            x: 0,
+            y: 0,
+            width: self.screen_width as i32,
+            height: self.screen_height as i32,
+        };
+
+        // Background
+        framebuffer.set_current_color(Color::new(10, 10, 20, 255));
+        framebuffer.draw_rectangle(0, 0, self.screen_width as i32, self.screen_height as i32);
+
+        // Maze
+        let exploration = if self.fog_of_war { Some(exploration) } else { None };
+        draw_maze_cells(
+            framebuffer,
+            &transform,
+            viewport,
+            maze,
+            materials,
+            self.block_size,
+            exploration,
+        );
+
+        // Waypoints
+        let marker_radius = (self.zoom / 4.0).max(3.0) as i32;
+        framebuffer.set_current_color(WAYPOINT_COLOR);
+        for (_, x, y) in waypoints.iter() {
+            let (sx, sy) = transform.world_to_minimap(x, y);
+            framebuffer.draw_circle(sx as i32, sy as i32, marker_radius);
+        }
+
+        // Player position and direction
+        let (px, py) = transform.world_to_minimap(player.pos.x, player.pos.y);
+        framebuffer.set_current_color(Color::RED);
+        framebuffer.draw_circle(px as i32, py as i32, marker_radius.max(4));
+
+        let line_length = self.zoom * 0.75;
+        framebuffer.set_current_color(Color::WHITE);
+        framebuffer.draw_line(
+            px as i32,
+            py as i32,
+            (px + line_length * player.a.cos()) as i32,
+            (py + line_length * player.a.sin()) as i32,
+        );
+    }
+
+    // Waypoint numbers and help text, drawn with raylib on top of the framebuffer
+    pub fn draw_labels(&self, d: &mut RaylibDrawHandle, waypoints: &Waypoints) {
+        let transform = self.transform();
+        let font_size = 16;
+
+        for (number, x, y) in waypoints.iter() {
+            let (sx, sy) = transform.world_to_minimap(x, y);
+            d.draw_text(
+                &number.to_string(),
+                sx as i32 - font_size / 4,
+                sy as i32 - font_size / 2,
+                font_size,
+                Color::BLACK,
+            );
+        }
+
+        d.draw_text("MAP", 20, 20, 24, Color::WHITE);
+        d.draw_text(
+            "WASD/Arrows - Pan   Wheel or +/- - Zoom   LMB - Waypoint   RMB - Remove   Q - Close",
+            20,
+            self.screen_height as i32 - 30,
+            16,
+            Color::LIGHTGRAY,
+        );
+    }
+}
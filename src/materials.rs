@@ -0,0 +1,212 @@
+// src/materials.rs
+use crate::resources::TextureFilter;
+use raylib::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MaterialError {
+    #[error("failed to read material manifest: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid material manifest: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+// Wall texture that cycles through frames (water, torches, blinking panels)
+#[derive(Debug, Clone, Deserialize)]
+pub struct Animation {
+    pub frames: Vec<String>,
+    pub fps: f32,
+}
+
+impl Animation {
+    pub fn frame_at(&self, time: f32) -> Option<&str> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        let frame = (time.max(0.0) * self.fps) as usize % self.frames.len();
+        Some(&self.frames[frame])
+    }
+}
+
+// What a maze glyph looks like and how it behaves. Extra manifest keys such as
+// "name" are for humans and ignored.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Material {
+    #[serde(default)]
+    pub wall_texture: Option<String>,
+    #[serde(default)]
+    pub animation: Option<Animation>, // Replaces wall_texture when present
+    #[serde(default)]
+    pub floor_texture: Option<String>,
+    #[serde(default)]
+    pub ceiling_texture: Option<String>,
+    #[serde(default)]
+    pub minimap_color: Option<[u8; 3]>,
+    pub walkable: bool, // The player can stand in the cell
+    pub solid: bool,    // Rays stop at the cell and it is drawn as a wall
+    #[serde(default = "default_height")]
+    pub height: f32, // Wall height in blocks, for solid cells
+    #[serde(default)]
+    pub elevation: f32, // Floor height in blocks, negative for pits
+    #[serde(default)]
+    pub transparent: bool, // Wall texels can be see-through (bars, fences, glass)
+    #[serde(default)]
+    pub door: bool, // A sliding door across the middle of the cell, drawn with wall_texture
+    #[serde(default)]
+    pub lock: Option<String>, // Doors that only open for the player holding this key
+    #[serde(default)]
+    pub key: Option<String>, // Key picked up by walking into the cell
+    #[serde(default)]
+    pub sprite: Option<String>, // Billboard standing in the cell, one block tall
+    #[serde(default)]
+    pub teleporter: bool, // Pad linked to the other cell with the same glyph
+    #[serde(default)]
+    pub pushwall: bool, // Secret wall that slides back when pushed
+    #[serde(default)]
+    pub damage: f32, // Health lost per second standing in the cell (spikes, lava)
+    #[serde(default)]
+    pub checkpoint: bool, // Records a split time and becomes the respawn point
+}
+
+impl Material {
+    // Wall texture path to show at `time` seconds into the level
+    pub fn wall_texture_at(&self, time: f32) -> Option<&str> {
+        match &self.animation {
+            Some(animation) => animation.frame_at(time),
+            None => self.wall_texture.as_deref(),
+        }
+    }
+
+    // Height in blocks of the cell's opaque upper surface: the top of the wall, or the
+    // floor for open and see-through cells
+    pub fn top(&self) -> f32 {
+        if self.blocks_sight() { self.elevation + self.height } else { self.elevation }
+    }
+
+    pub fn blocks_sight(&self) -> bool {
+        self.solid && !self.transparent
+    }
+
+    pub fn minimap_color(&self) -> Option<Color> {
+        self.minimap_color.map(|[r, g, b]| Color::new(r, g, b, 255))
+    }
+}
+
+fn default_height() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+struct MaterialEntry {
+    glyph: char,
+    #[serde(flatten)]
+    material: Material,
+}
+
+// How every texture is sampled
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TextureSettings {
+    #[serde(default)]
+    pub filter: TextureFilter,
+    #[serde(default = "default_mipmaps")]
+    pub mipmaps: bool,
+}
+
+impl Default for TextureSettings {
+    fn default() -> Self {
+        Self { filter: TextureFilter::default(), mipmaps: default_mipmaps() }
+    }
+}
+
+fn default_mipmaps() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    textures: TextureSettings,
+    default: Material,
+    materials: Vec<MaterialEntry>,
+}
+
+// Single glyph -> material lookup consulted by every module
+pub struct MaterialRegistry {
+    materials: HashMap<char, Material>,
+    default: Material,
+    texture_settings: TextureSettings,
+}
+
+impl MaterialRegistry {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MaterialError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_json(&text)
+    }
+
+    // The manifest shipped with the game, compiled in as a fallback
+    pub fn builtin() -> Self {
+        Self::from_json(include_str!("../materials.json"))
+            .expect("built-in materials.json is valid")
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, MaterialError> {
+        let manifest: Manifest = serde_json::from_str(text)?;
+        let materials =
+            manifest.materials.into_iter().map(|entry| (entry.glyph, entry.material)).collect();
+
+        Ok(Self { materials, default: manifest.default, texture_settings: manifest.textures })
+    }
+
+    // Unknown glyphs fall back to the manifest's default material
+    pub fn get(&self, glyph: char) -> &Material {
+        self.materials.get(&glyph).unwrap_or(&self.default)
+    }
+
+    pub fn is_walkable(&self, glyph: char) -> bool {
+        self.get(glyph).walkable
+    }
+
+    pub fn blocks_sight(&self, glyph: char) -> bool {
+        self.get(glyph).blocks_sight()
+    }
+
+    // Colour shown for a key in the HUD: the map colour of the cell holding it
+    pub fn key_color(&self, key: &str) -> Color {
+        self.materials
+            .values()
+            .find(|material| material.key.as_deref() == Some(key))
+            .and_then(Material::minimap_color)
+            .unwrap_or(Color::WHITE)
+    }
+
+    pub fn texture_settings(&self) -> TextureSettings {
+        self.texture_settings
+    }
+
+    // Every texture path referenced by any material
+    pub fn texture_paths(&self) -> Vec<&str> {
+        let mut paths: Vec<&str> = self
+            .materials
+            .values()
+            .chain(std::iter::once(&self.default))
+            .flat_map(|material| {
+                let frames = material.animation.iter().flat_map(|animation| &animation.frames);
+                [
+                    &material.wall_texture,
+                    &material.floor_texture,
+                    &material.ceiling_texture,
+                    &material.sprite,
+                ]
+                .into_iter()
+                .filter_map(|path| path.as_deref())
+                .chain(frames.map(String::as_str))
+            })
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+        paths
+    }
+}
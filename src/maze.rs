@@ -1,23 +1,31 @@
 // src/maze.rs
+use crate::assets::AssetError;
 use crate::framebuffer::Framebuffer;
+use crate::materials::MaterialRegistry;
 use raylib::prelude::*;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::path::Path;
 
-pub fn load_maze(filename: &str) -> Vec<Vec<char>> {
-    let file = File::open(filename).unwrap();
+pub fn load_maze(path: &Path) -> Result<Vec<Vec<char>>, AssetError> {
+    let io_error = |source| AssetError::Io { path: path.to_path_buf(), source };
+
+    let file = File::open(path).map_err(io_error)?;
     let reader = BufReader::new(file);
 
-    reader.lines().map(|line| line.unwrap().chars().collect()).collect()
+    reader.lines().map(|line| line.map(|line| line.chars().collect()).map_err(io_error)).collect()
 }
 
-fn draw_cell(framebuffer: &mut Framebuffer, x0: usize, y0: usize, block_size: usize, cell: char) {
-    let color = match cell {
-        '+' | '|' | '-' => Color::BLUE, // paredes azules
-        ' ' => Color::WHITE,            // espacios blancos
-        'p' | 'g' => Color::RED,        // inicio y meta rojos
-        _ => Color::GRAY,               // cualquier otro caracter
-    };
+fn draw_cell(
+    framebuffer: &mut Framebuffer,
+    x0: usize,
+    y0: usize,
+    block_size: usize,
+    cell: char,
+    materials: &MaterialRegistry,
+) {
+    // color del material, blanco si no tiene
+    let color = materials.get(cell).minimap_color().unwrap_or(Color::WHITE);
 
     framebuffer.set_current_color(color);
 
@@ -30,12 +38,17 @@ fn draw_cell(framebuffer: &mut Framebuffer, x0: usize, y0: usize, block_size: us
     }
 }
 
-pub fn render_maze(framebuffer: &mut Framebuffer, maze: &Vec<Vec<char>>, block_size: usize) {
+pub fn render_maze(
+    framebuffer: &mut Framebuffer,
+    maze: &Vec<Vec<char>>,
+    materials: &MaterialRegistry,
+    block_size: usize,
+) {
     for (row_index, row) in maze.iter().enumerate() {
         for (col_index, &cell) in row.iter().enumerate() {
             let x0 = col_index * block_size;
             let y0 = row_index * block_size;
-            draw_cell(framebuffer, x0, y0, block_size, cell);
+            draw_cell(framebuffer, x0, y0, block_size, cell, materials);
         }
     }
 }
@@ -1,28 +1,175 @@
 // src/minimap.rs
-use crate::framebuffer::Framebuffer;
+use crate::exploration::ExplorationMap;
+use crate::framebuffer::{ClipRegion, Framebuffer};
+use crate::materials::MaterialRegistry;
 use crate::player::Player;
+use crate::waypoints::{WAYPOINT_COLOR, Waypoints};
 use raylib::prelude::*;
 use std::f32::consts::PI;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinimapFit {
+    Fit,  // Scale the whole maze down to fit inside the minimap box
+    Clip, // Keep a fixed scale and clip whatever falls outside the box
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinimapMode {
+    Overview, // Static view of the maze (fit or clipped)
+    Follow,   // Scrolls with the player at a fixed zoom
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinimapShape {
+    Square,
+    Circle,
+}
+
+// World -> minimap mapping shared by every layer. Rotates around world_x/world_y,
+// which lands on screen_x/screen_y.
+#[derive(Debug, Clone, Copy)]
+pub struct MinimapTransform {
+    pub scale: f32, // world units per minimap pixel
+    pub rotation: f32,
+    pub world_x: f32,
+    pub world_y: f32,
+    pub screen_x: f32,
+    pub screen_y: f32,
+}
+
+impl MinimapTransform {
+    pub fn world_to_minimap(&self, world_x: f32, world_y: f32) -> (f32, f32) {
+        let dx = (world_x - self.world_x) / self.scale;
+        let dy = (world_y - self.world_y) / self.scale;
+        let (sin, cos) = self.rotation.sin_cos();
+        (self.screen_x + dx * cos - dy * sin, self.screen_y + dx * sin + dy * cos)
+    }
+
+    pub fn minimap_to_world(&self, minimap_x: f32, minimap_y: f32) -> (f32, f32) {
+        let dx = minimap_x - self.screen_x;
+        let dy = minimap_y - self.screen_y;
+        let (sin, cos) = self.rotation.sin_cos();
+        (
+            self.world_x + (dx * cos + dy * sin) * self.scale,
+            self.world_y + (dy * cos - dx * sin) * self.scale,
+        )
+    }
+}
+
 pub struct Minimap {
     pub size: u32,
     pub offset_x: u32,
     pub offset_y: u32,
-    pub scale: f32,
+    pub scale: f32, // Used in Clip mode only
+    pub fit: MinimapFit,
+    pub mode: MinimapMode,
+    pub shape: MinimapShape,
+    pub rotate: bool, // Follow mode: keep the player's forward direction pointing up
+    pub zoom: f32,    // Follow mode: minimap pixels per maze cell
+    pub fog_of_war: bool, // Only draw cells the player has already seen
+    pub transform: MinimapTransform,
+    pub block_size: usize,
+    goal: Option<(f32, f32)>,
 }
 
 impl Minimap {
-    pub fn new(screen_width: u32, screen_height: u32) -> Self {
+    pub const MIN_ZOOM: f32 = 8.0;
+    pub const MAX_ZOOM: f32 = 64.0;
+
+    pub fn new(maze: &[Vec<char>], block_size: usize) -> Self {
         let size = 210;
         let padding = 20;
         let offset_x = padding; // Move to left side
         let offset_y = padding;
 
-        Self {
+        let mut minimap = Self {
             size,
             offset_x,
             offset_y,
-            scale: 3.2, // Reduced scale to prevent overflow
+            scale: 3.2,
+            fit: MinimapFit::Fit,
+            mode: MinimapMode::Overview,
+            shape: MinimapShape::Square,
+            rotate: true,
+            zoom: 24.0,
+            fog_of_war: true,
+            transform: MinimapTransform {
+                scale: 1.0,
+                rotation: 0.0,
+                world_x: 0.0,
+                world_y: 0.0,
+                screen_x: 0.0,
+                screen_y: 0.0,
+            },
+            block_size,
+            goal: find_goal(maze, block_size),
+        };
+        minimap.update_transform(maze);
+        minimap
+    }
+
+    // Recompute the overview transform from the maze dimensions (call again when the maze changes)
+    pub fn update_transform(&mut self, maze: &[Vec<char>]) {
+        let maze_width = maze.iter().map(|row| row.len()).max().unwrap_or(0) * self.block_size;
+        let maze_height = maze.len() * self.block_size;
+        let longest_side = maze_width.max(maze_height).max(1) as f32;
+
+        let scale = match self.fit {
+            MinimapFit::Fit => longest_side / self.size as f32,
+            MinimapFit::Clip => self.scale,
+        };
+
+        // Center the maze in the minimap
+        let (screen_x, screen_y) = self.center();
+        self.transform = MinimapTransform {
+            scale,
+            rotation: 0.0,
+            world_x: maze_width as f32 / 2.0,
+            world_y: maze_height as f32 / 2.0,
+            screen_x,
+            screen_y,
+        };
+    }
+
+    pub fn set_fit(&mut self, fit: MinimapFit, maze: &[Vec<char>]) {
+        self.fit = fit;
+        self.update_transform(maze);
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            MinimapMode::Overview => MinimapMode::Follow,
+            MinimapMode::Follow => MinimapMode::Overview,
+        };
+    }
+
+    pub fn toggle_shape(&mut self) {
+        self.shape = match self.shape {
+            MinimapShape::Square => MinimapShape::Circle,
+            MinimapShape::Circle => MinimapShape::Square,
+        };
+    }
+
+    pub fn adjust_zoom(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+    }
+
+    // Transform used for this frame: the cached overview one, or one centred on the player
+    fn frame_transform(&self, player: &Player) -> MinimapTransform {
+        match self.mode {
+            MinimapMode::Overview => self.transform,
+            MinimapMode::Follow => {
+                let (screen_x, screen_y) = self.center();
+                let rotation = if self.rotate { -PI / 2.0 - player.a } else { 0.0 };
+                MinimapTransform {
+                    scale: self.block_size as f32 / self.zoom,
+                    rotation,
+                    world_x: player.pos.x,
+                    world_y: player.pos.y,
+                    screen_x,
+                    screen_y,
+                }
+            }
         }
     }
 
@@ -30,85 +177,125 @@ impl Minimap {
         &self,
         framebuffer: &mut Framebuffer,
         player: &Player,
-        maze: &Vec<Vec<char>>,
-        block_size: usize,
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        exploration: &ExplorationMap,
+        waypoints: &Waypoints,
     ) {
+        let transform = self.frame_transform(player);
+
         // Draw minimap background (semi-transparent black)
         self.draw_background(framebuffer);
 
+        // Keep every layer inside the minimap viewport
+        framebuffer.set_clip(self.viewport());
+
         // Draw maze walls
-        self.draw_maze(framebuffer, maze, block_size);
+        self.draw_maze(framebuffer, &transform, maze, materials, exploration);
+
+        // Draw waypoints placed from the full map
+        self.draw_waypoints(framebuffer, &transform, waypoints);
 
         // Draw player FOV cone
-        self.draw_fov_cone(framebuffer, player, block_size);
+        self.draw_fov_cone(framebuffer, &transform, player);
 
         // Draw player position and direction
-        self.draw_player(framebuffer, player, block_size);
+        self.draw_player(framebuffer, &transform, player);
+
+        framebuffer.clear_clip();
+
+        // Point toward the goal when it is off the map
+        self.draw_goal_indicator(framebuffer, &transform, exploration);
 
         // Draw border around minimap
         self.draw_border(framebuffer);
     }
 
+    fn center(&self) -> (f32, f32) {
+        (
+            self.offset_x as f32 + self.size as f32 / 2.0,
+            self.offset_y as f32 + self.size as f32 / 2.0,
+        )
+    }
+
+    fn viewport(&self) -> ClipRegion {
+        match self.shape {
+            MinimapShape::Square => ClipRegion::Rect {
+                x: self.offset_x as i32,
+                y: self.offset_y as i32,
+                width: self.size as i32,
+                height: self.size as i32,
+            },
+            MinimapShape::Circle => ClipRegion::Circle {
+                center_x: (self.offset_x + self.size / 2) as i32,
+                center_y: (self.offset_y + self.size / 2) as i32,
+                radius: (self.size / 2) as i32,
+            },
+        }
+    }
+
     fn draw_background(&self, framebuffer: &mut Framebuffer) {
         framebuffer.set_current_color(Color::new(0, 0, 0, 180));
-        framebuffer.draw_rectangle(
-            self.offset_x as i32,
-            self.offset_y as i32,
-            self.size as i32,
-            self.size as i32,
+        match self.shape {
+            MinimapShape::Square => framebuffer.draw_rectangle(
+                self.offset_x as i32,
+                self.offset_y as i32,
+                self.size as i32,
+                self.size as i32,
+            ),
+            MinimapShape::Circle => framebuffer.draw_circle(
+                (self.offset_x + self.size / 2) as i32,
+                (self.offset_y + self.size / 2) as i32,
+                (self.size / 2) as i32,
+            ),
+        }
+    }
+
+    fn draw_maze(
+        &self,
+        framebuffer: &mut Framebuffer,
+        transform: &MinimapTransform,
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        exploration: &ExplorationMap,
+    ) {
+        let exploration = if self.fog_of_war { Some(exploration) } else { None };
+        let viewport = self.viewport();
+        draw_maze_cells(
+            framebuffer,
+            transform,
+            viewport,
+            maze,
+            materials,
+            self.block_size,
+            exploration,
         );
     }
 
-    fn draw_maze(&self, framebuffer: &mut Framebuffer, maze: &Vec<Vec<char>>, block_size: usize) {
-        let maze_width = maze[0].len() * block_size;
-        let maze_height = maze.len() * block_size;
-
-        // Calculate center offset to center the maze in the minimap
-        let center_offset_x = (self.size as f32 - maze_width as f32 / self.scale) / 2.0;
-        let center_offset_y = (self.size as f32 - maze_height as f32 / self.scale) / 2.0;
-
-        for (row_idx, row) in maze.iter().enumerate() {
-            for (col_idx, &cell) in row.iter().enumerate() {
-                if cell != ' ' && cell != 'p' {
-                    let world_x = (col_idx * block_size) as f32;
-                    let world_y = (row_idx * block_size) as f32;
-
-                    let minimap_x = self.offset_x as f32 + center_offset_x + world_x / self.scale;
-                    let minimap_y = self.offset_y as f32 + center_offset_y + world_y / self.scale;
-                    let cell_size = ((block_size as f32 / self.scale) as i32).max(2);
-
-                    if self.is_in_minimap_bounds(minimap_x as i32, minimap_y as i32) {
-                        let color = match cell {
-                            '#' => Color::DARKGRAY,
-                            '*' => Color::GRAY,
-                            'g' => Color::GOLD,
-                            _ => Color::BLUE, // Default wall color
-                        };
-
-                        framebuffer.set_current_color(color);
-                        framebuffer.draw_rectangle(
-                            minimap_x as i32,
-                            minimap_y as i32,
-                            cell_size,
-                            cell_size,
-                        );
-                    }
-                }
-            }
+    fn draw_waypoints(
+        &self,
+        framebuffer: &mut Framebuffer,
+        transform: &MinimapTransform,
+        waypoints: &Waypoints,
+    ) {
+        framebuffer.set_current_color(WAYPOINT_COLOR);
+        for (_, x, y) in waypoints.iter() {
+            let (minimap_x, minimap_y) = transform.world_to_minimap(x, y);
+            framebuffer.draw_circle(minimap_x as i32, minimap_y as i32, 3);
         }
     }
 
-    fn draw_fov_cone(&self, framebuffer: &mut Framebuffer, player: &Player, block_size: usize) {
-        let maze_width = 9 * block_size; // Assuming your maze is roughly 9 units wide
-        let maze_height = 9 * block_size; // Assuming your maze is roughly 9 units tall
-
-        let center_offset_x = (self.size as f32 - maze_width as f32 / self.scale) / 2.0;
-        let center_offset_y = (self.size as f32 - maze_height as f32 / self.scale) / 2.0;
-
-        let player_minimap_x = self.offset_x as f32 + center_offset_x + player.pos.x / self.scale;
-        let player_minimap_y = self.offset_y as f32 + center_offset_y + player.pos.y / self.scale;
+    fn draw_fov_cone(
+        &self,
+        framebuffer: &mut Framebuffer,
+        transform: &MinimapTransform,
+        player: &Player,
+    ) {
+        let (player_minimap_x, player_minimap_y) =
+            transform.world_to_minimap(player.pos.x, player.pos.y);
 
-        let cone_length = 50.0;
+        // Cone length is in minimap pixels, whatever the zoom
+        let cone_length = 50.0 * transform.scale;
         let half_fov = player.fov / 2.0;
 
         // Draw FOV cone lines
@@ -116,8 +303,10 @@ impl Minimap {
 
         // Left edge of FOV
         let left_angle = player.a - half_fov;
-        let left_end_x = player_minimap_x + cone_length * left_angle.cos();
-        let left_end_y = player_minimap_y + cone_length * left_angle.sin();
+        let (left_end_x, left_end_y) = transform.world_to_minimap(
+            player.pos.x + cone_length * left_angle.cos(),
+            player.pos.y + cone_length * left_angle.sin(),
+        );
 
         framebuffer.draw_line(
             player_minimap_x as i32,
@@ -128,8 +317,10 @@ impl Minimap {
 
         // Right edge of FOV
         let right_angle = player.a + half_fov;
-        let right_end_x = player_minimap_x + cone_length * right_angle.cos();
-        let right_end_y = player_minimap_y + cone_length * right_angle.sin();
+        let (right_end_x, right_end_y) = transform.world_to_minimap(
+            player.pos.x + cone_length * right_angle.cos(),
+            player.pos.y + cone_length * right_angle.sin(),
+        );
 
         framebuffer.draw_line(
             player_minimap_x as i32,
@@ -139,36 +330,96 @@ impl Minimap {
         );
     }
 
-    fn draw_player(&self, framebuffer: &mut Framebuffer, player: &Player, block_size: usize) {
-        let maze_width = 9 * block_size; // Assuming your maze is roughly 9 units wide
-        let maze_height = 9 * block_size; // Assuming your maze is roughly 9 units tall
-
-        let center_offset_x = (self.size as f32 - maze_width as f32 / self.scale) / 2.0;
-        let center_offset_y = (self.size as f32 - maze_height as f32 / self.scale) / 2.0;
-
-        let player_minimap_x =
-            (self.offset_x as f32 + center_offset_x + player.pos.x / self.scale) as i32;
-        let player_minimap_y =
-            (self.offset_y as f32 + center_offset_y + player.pos.y / self.scale) as i32;
+    fn draw_player(
+        &self,
+        framebuffer: &mut Framebuffer,
+        transform: &MinimapTransform,
+        player: &Player,
+    ) {
+        let (player_minimap_x, player_minimap_y) =
+            transform.world_to_minimap(player.pos.x, player.pos.y);
+        let player_minimap_x = player_minimap_x as i32;
+        let player_minimap_y = player_minimap_y as i32;
 
-        if self.is_in_minimap_bounds(player_minimap_x, player_minimap_y) {
+        if self.viewport().contains(player_minimap_x, player_minimap_y) {
             // Draw player as a red circle
             framebuffer.set_current_color(Color::RED);
             framebuffer.draw_circle(player_minimap_x, player_minimap_y, 4);
 
             // Draw direction line
-            let line_length = 15.0;
-            let end_x = player_minimap_x as f32 + line_length * player.a.cos();
-            let end_y = player_minimap_y as f32 + line_length * player.a.sin();
+            let line_length = 15.0 * transform.scale;
+            let (end_x, end_y) = transform.world_to_minimap(
+                player.pos.x + line_length * player.a.cos(),
+                player.pos.y + line_length * player.a.sin(),
+            );
 
             framebuffer.set_current_color(Color::WHITE);
             framebuffer.draw_line(player_minimap_x, player_minimap_y, end_x as i32, end_y as i32);
         }
     }
 
+    fn draw_goal_indicator(
+        &self,
+        framebuffer: &mut Framebuffer,
+        transform: &MinimapTransform,
+        exploration: &ExplorationMap,
+    ) {
+        let Some((goal_x, goal_y)) = self.goal else {
+            return;
+        };
+
+        // Don't give away a goal the player hasn't found yet
+        let goal_col = goal_x as usize / self.block_size;
+        let goal_row = goal_y as usize / self.block_size;
+        if self.fog_of_war && !exploration.is_discovered(goal_col, goal_row) {
+            return;
+        }
+
+        let (goal_minimap_x, goal_minimap_y) = transform.world_to_minimap(goal_x, goal_y);
+        if self.viewport().contains(goal_minimap_x as i32, goal_minimap_y as i32) {
+            return;
+        }
+
+        let (center_x, center_y) = self.center();
+        let dx = goal_minimap_x - center_x;
+        let dy = goal_minimap_y - center_y;
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            return;
+        }
+        let (dir_x, dir_y) = (dx / length, dy / length);
+
+        // Pull the marker back onto the viewport edge
+        let inset = self.size as f32 / 2.0 - 8.0;
+        let reach = match self.shape {
+            MinimapShape::Circle => inset,
+            MinimapShape::Square => inset / dir_x.abs().max(dir_y.abs()),
+        };
+        let base_x = center_x + dir_x * reach;
+        let base_y = center_y + dir_y * reach;
+
+        // Arrow head pointing toward the goal
+        let tip = (base_x + dir_x * 6.0, base_y + dir_y * 6.0);
+        let left = (base_x - dir_x * 4.0 - dir_y * 5.0, base_y - dir_y * 4.0 + dir_x * 5.0);
+        let right = (base_x - dir_x * 4.0 + dir_y * 5.0, base_y - dir_y * 4.0 - dir_x * 5.0);
+
+        framebuffer.set_current_color(Color::GOLD);
+        framebuffer.fill_triangle(tip, left, right);
+    }
+
     fn draw_border(&self, framebuffer: &mut Framebuffer) {
         framebuffer.set_current_color(Color::WHITE);
 
+        if self.shape == MinimapShape::Circle {
+            framebuffer.draw_circle_outline(
+                (self.offset_x + self.size / 2) as i32,
+                (self.offset_y + self.size / 2) as i32,
+                (self.size / 2) as i32 + 1,
+                2,
+            );
+            return;
+        }
+
         // Top border
         framebuffer.draw_rectangle(
             self.offset_x as i32 - 1,
@@ -201,19 +452,72 @@ impl Minimap {
             self.size as i32 + 2,
         );
     }
+}
 
-    fn world_to_minimap_x(&self, world_x: f32) -> i32 {
-        (self.offset_x as f32 + world_x / self.scale) as i32
+// Colour legend shared by every map view, taken from the material registry. Open floor
+// is only drawn once explored so the fog stands out; None leaves the pixel untouched.
+pub fn cell_color(cell: char, explored: bool, materials: &MaterialRegistry) -> Option<Color> {
+    let material = materials.get(cell);
+    if !material.solid && !explored {
+        return None;
     }
+    material.minimap_color()
+}
+
+// Fill a viewport with the maze by mapping every pixel back into the maze, so rotation
+// needs no polygon filling. With an exploration map only discovered cells are drawn.
+pub fn draw_maze_cells(
+    framebuffer: &mut Framebuffer,
+    transform: &MinimapTransform,
+    viewport: ClipRegion,
+    maze: &[Vec<char>],
+    materials: &MaterialRegistry,
+    block_size: usize,
+    exploration: Option<&ExplorationMap>,
+) {
+    let (x0, y0, width, height) = viewport.bounds();
+
+    for py in y0.max(0)..y0 + height {
+        for px in x0.max(0)..x0 + width {
+            if !viewport.contains(px, py) {
+                continue;
+            }
+
+            let (world_x, world_y) = transform.minimap_to_world(px as f32 + 0.5, py as f32 + 0.5);
+            if world_x < 0.0 || world_y < 0.0 {
+                continue;
+            }
+
+            let col_idx = world_x as usize / block_size;
+            let row_idx = world_y as usize / block_size;
+            let Some(&cell) = maze.get(row_idx).and_then(|row| row.get(col_idx)) else {
+                continue;
+            };
+
+            if let Some(map) = exploration
+                && !map.is_discovered(col_idx, row_idx)
+            {
+                continue;
+            }
 
-    fn world_to_minimap_y(&self, world_y: f32) -> i32 {
-        (self.offset_y as f32 + world_y / self.scale) as i32
+            if let Some(color) = cell_color(cell, exploration.is_some(), materials) {
+                framebuffer.set_current_color(color);
+                framebuffer.set_pixel(px as u32, py as u32);
+            }
+        }
     }
+}
 
-    fn is_in_minimap_bounds(&self, x: i32, y: i32) -> bool {
-        x >= self.offset_x as i32
-            && x < (self.offset_x + self.size) as i32
-            && y >= self.offset_y as i32
-            && y < (self.offset_y + self.size) as i32
+// Centre of the first goal cell in world coordinates
+fn find_goal(maze: &[Vec<char>], block_size: usize) -> Option<(f32, f32)> {
+    for (row_idx, row) in maze.iter().enumerate() {
+        for (col_idx, &cell) in row.iter().enumerate() {
+            if cell == 'g' {
+                let x = (col_idx * block_size + block_size / 2) as f32;
+                let y = (row_idx * block_size + block_size / 2) as f32;
+                return Some((x, y));
+            }
+        }
     }
+    None
 }
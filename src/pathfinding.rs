@@ -0,0 +1,60 @@
+// src/pathfinding.rs
+use crate::materials::MaterialRegistry;
+use crate::teleporters::pair_pads;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub type Cell = (usize, usize); // (col, row)
+
+// Walkable cells as a graph. Each cell links to its passable neighbours, and teleporter
+// pads also link to their partner pad. Pushwalls count as passable since the player can
+// push them out of the way.
+pub struct NavGraph<'a> {
+    maze: &'a Vec<Vec<char>>,
+    materials: &'a MaterialRegistry,
+    teleports: HashMap<Cell, Cell>,
+}
+
+impl<'a> NavGraph<'a> {
+    pub fn new(maze: &'a Vec<Vec<char>>, materials: &'a MaterialRegistry) -> Self {
+        Self { maze, materials, teleports: pair_pads(maze, materials) }
+    }
+
+    pub fn glyph(&self, (col, row): Cell) -> Option<char> {
+        self.maze.get(row).and_then(|cells| cells.get(col)).copied()
+    }
+
+    pub fn is_passable(&self, cell: Cell) -> bool {
+        self.glyph(cell).is_some_and(|glyph| {
+            self.materials.is_walkable(glyph) || self.materials.get(glyph).pushwall
+        })
+    }
+
+    pub fn neighbours(&self, (col, row): Cell) -> Vec<Cell> {
+        let mut cells = vec![(col + 1, row), (col, row + 1)];
+        if let Some(col) = col.checked_sub(1) {
+            cells.push((col, row));
+        }
+        if let Some(row) = row.checked_sub(1) {
+            cells.push((col, row));
+        }
+        cells.retain(|&cell| self.is_passable(cell));
+        cells.extend(self.teleports.get(&(col, row)));
+        cells
+    }
+
+    // Every cell reachable from `start`, entering only cells `can_enter` allows
+    pub fn reachable(&self, start: Cell, can_enter: impl Fn(Cell) -> bool) -> HashSet<Cell> {
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(cell) = queue.pop_front() {
+            for next in self.neighbours(cell) {
+                if can_enter(next) && seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        seen
+    }
+}
@@ -0,0 +1,116 @@
+// src/pause_menu.rs
+use crate::settings::Settings;
+use raylib::prelude::*;
+
+// What the player picked in the pause menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PauseAction {
+    Resume,
+    Restart,
+    QuitToMenu,
+    RenderScale(f32), // Steps to change the render scale by
+    Fov(f32),         // Steps to change the field of view by
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PausePage {
+    Main,
+    Settings,
+}
+
+// Menu drawn over the frozen game while paused
+pub struct PauseMenu {
+    pub page: PausePage,
+    pub selected: usize,
+    just_opened: bool, // The key that paused is still down on the first frame
+}
+
+impl PauseMenu {
+    pub const MAIN_ITEMS: [&'static str; 4] = ["Resume", "Restart", "Settings", "Quit to Menu"];
+    pub const SETTINGS_ITEMS: usize = 3; // Render scale, FOV, Back
+
+    pub fn new() -> Self {
+        Self { page: PausePage::Main, selected: 0, just_opened: false }
+    }
+
+    pub fn open(&mut self) {
+        self.page = PausePage::Main;
+        self.selected = 0;
+        self.just_opened = true;
+    }
+
+    // True only on the first frame after `open`
+    pub fn take_just_opened(&mut self) -> bool {
+        std::mem::take(&mut self.just_opened)
+    }
+
+    pub fn item_count(&self) -> usize {
+        match self.page {
+            PausePage::Main => Self::MAIN_ITEMS.len(),
+            PausePage::Settings => Self::SETTINGS_ITEMS,
+        }
+    }
+
+    // Move the highlight, wrapping around at either end
+    pub fn select(&mut self, steps: i32) {
+        let count = self.item_count() as i32;
+        self.selected = (self.selected as i32 + steps).rem_euclid(count) as usize;
+    }
+
+    pub fn open_page(&mut self, page: PausePage) {
+        self.page = page;
+        self.selected = 0;
+    }
+
+    pub fn draw(
+        &self,
+        d: &mut RaylibDrawHandle,
+        settings: &Settings,
+        screen_width: i32,
+        screen_height: i32,
+    ) {
+        d.draw_rectangle(0, 0, screen_width, screen_height, Color::new(0, 0, 0, 160));
+
+        let (title, items) = match self.page {
+            PausePage::Main => {
+                ("PAUSED", Self::MAIN_ITEMS.iter().map(|item| item.to_string()).collect())
+            }
+            PausePage::Settings => (
+                "SETTINGS",
+                vec![
+                    format!("Render Scale: < {:.0}% >", settings.render_scale * 100.0),
+                    format!("FOV: < {:.0} >", settings.fov_degrees),
+                    "Back".to_string(),
+                ],
+            ),
+        };
+
+        let title_width = (title.len() as i32 * 45) / 2;
+        d.draw_text(
+            title,
+            screen_width / 2 - title_width / 2,
+            screen_height / 2 - 120,
+            45,
+            Color::WHITE,
+        );
+
+        for (index, item) in items.iter().enumerate() {
+            let selected = index == self.selected;
+            let label = if selected { format!("> {} <", item) } else { item.clone() };
+            let width = (label.len() as i32 * 24) / 2;
+            d.draw_text(
+                &label,
+                screen_width / 2 - width / 2,
+                screen_height / 2 - 40 + index as i32 * 36,
+                24,
+                if selected { Color::GOLD } else { Color::LIGHTGRAY },
+            );
+        }
+
+        let hint = match self.page {
+            PausePage::Main => "Up/Down - Select   Enter - Choose   P - Resume",
+            PausePage::Settings => "Left/Right - Change   Backspace - Back",
+        };
+        d.draw_text(hint, screen_width / 2 - 220, screen_height - 40, 18, Color::DARKGRAY);
+    }
+}
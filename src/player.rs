@@ -1,4 +1,5 @@
 // src/player.rs
+use crate::materials::MaterialRegistry;
 use raylib::prelude::*;
 use std::f32::consts::PI;
 
@@ -6,11 +7,72 @@ pub struct Player {
     pub pos: Vector2,
     pub a: f32, // angle
     pub fov: f32,
+    pub pitch: f32,     // Horizon shift in half-screens, positive looks up
+    pub elevation: f32, // Height of the floor under the player, in blocks
+    pub health: f32,
 }
 
 impl Player {
+    pub const MAX_PITCH: f32 = 0.8;
+    pub const EYE_HEIGHT: f32 = 0.5; // Above the floor, in blocks
+    pub const MAX_HEALTH: f32 = 100.0;
+
     pub fn new(x: f32, y: f32) -> Self {
-        Self { pos: Vector2::new(x, y), a: PI / 3.0, fov: PI / 3.0 }
+        Self {
+            pos: Vector2::new(x, y),
+            a: PI / 3.0,
+            fov: PI / 3.0,
+            pitch: 0.0,
+            elevation: 0.0,
+            health: Self::MAX_HEALTH,
+        }
+    }
+
+    pub fn eye_height(&self) -> f32 {
+        self.elevation + Self::EYE_HEIGHT
+    }
+
+    // Ease towards the floor of the current cell so steps and pits don't snap the view
+    pub fn follow_floor(
+        &mut self,
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        block_size: usize,
+    ) {
+        const FOLLOW_RATE: f32 = 0.2;
+
+        let col = self.pos.x as usize / block_size;
+        let row = self.pos.y as usize / block_size;
+        if let Some(&cell) = maze.get(row).and_then(|cells| cells.get(col)) {
+            let target = materials.get(cell).elevation;
+            self.elevation += (target - self.elevation) * FOLLOW_RATE;
+        }
+    }
+
+    // Hurt the player for `dt` seconds spent on the current cell. Returns the damage taken.
+    pub fn take_hazard_damage(
+        &mut self,
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        block_size: usize,
+        dt: f32,
+    ) -> f32 {
+        let col = self.pos.x as usize / block_size;
+        let row = self.pos.y as usize / block_size;
+        let Some(&cell) = maze.get(row).and_then(|cells| cells.get(col)) else {
+            return 0.0;
+        };
+        let damage = materials.get(cell).damage * dt;
+        self.health = (self.health - damage).max(0.0);
+        damage
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
+    }
+
+    pub fn look(&mut self, amount: f32) {
+        self.pitch = (self.pitch + amount).clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
     }
 
     pub fn from_maze(maze: &Vec<Vec<char>>, block_size: usize) -> Option<Self> {
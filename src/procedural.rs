@@ -0,0 +1,179 @@
+// src/procedural.rs
+use raylib::prelude::*;
+
+// Generated stand-ins for textures that failed to load
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placeholder {
+    Checker,
+    Brick,
+    Noise,
+    Bars,
+    Glass,
+    Planks,
+    Key,
+    Spikes,
+    Lava,
+}
+
+impl Placeholder {
+    // Guess a fitting pattern from the missing file's name
+    pub fn for_path(path: &str) -> Self {
+        let name = path.to_lowercase();
+        if name.contains("key") {
+            Placeholder::Key
+        } else if name.contains("spike") {
+            Placeholder::Spikes
+        } else if name.contains("lava") || name.contains("magma") {
+            Placeholder::Lava
+        } else if name.contains("brick") {
+            Placeholder::Brick
+        } else if name.contains("bars") || name.contains("fence") || name.contains("grate") {
+            Placeholder::Bars
+        } else if name.contains("glass") || name.contains("window") {
+            Placeholder::Glass
+        } else if name.contains("door") || name.contains("wood") || name.contains("plank") {
+            Placeholder::Planks
+        } else if name.contains("stone") || name.contains("rock") || name.contains("metal") {
+            Placeholder::Noise
+        } else {
+            Placeholder::Checker
+        }
+    }
+
+    // Square texture of `size` pixels, row-major
+    pub fn generate(self, size: usize) -> Vec<Color> {
+        let mut pixels = Vec::with_capacity(size * size);
+        for y in 0..size {
+            for x in 0..size {
+                pixels.push(match self {
+                    Placeholder::Checker => checker(x, y, size),
+                    Placeholder::Brick => brick(x, y, size),
+                    Placeholder::Noise => noise(x, y),
+                    Placeholder::Bars => bars(x, y, size),
+                    Placeholder::Glass => glass(x, y, size),
+                    Placeholder::Planks => planks(x, y, size),
+                    Placeholder::Key => key(x, y, size),
+                    Placeholder::Spikes => spikes(x, y, size),
+                    Placeholder::Lava => lava(x, y, size),
+                });
+            }
+        }
+        pixels
+    }
+}
+
+// Classic magenta/black "missing texture" pattern
+fn checker(x: usize, y: usize, size: usize) -> Color {
+    let cell = (size / 8).max(1);
+    if (x / cell + y / cell).is_multiple_of(2) { Color::MAGENTA } else { Color::BLACK }
+}
+
+fn brick(x: usize, y: usize, size: usize) -> Color {
+    let brick_height = (size / 4).max(2);
+    let brick_width = (size / 2).max(2);
+    let row = y / brick_height;
+
+    // Every other row is offset by half a brick
+    let offset = if row.is_multiple_of(2) { 0 } else { brick_width / 2 };
+    let mortar = y.is_multiple_of(brick_height) || (x + offset).is_multiple_of(brick_width);
+
+    if mortar {
+        Color::new(170, 170, 160, 255)
+    } else {
+        let shade = (hash(x / brick_width, row) % 40) as u8;
+        Color::new(140 + shade, 50 + shade / 2, 40, 255)
+    }
+}
+
+fn noise(x: usize, y: usize) -> Color {
+    let value = 90 + (hash(x, y) % 70) as u8;
+    Color::new(value, value, value, 255)
+}
+
+// Iron bars with a rail top and bottom; the gaps are fully transparent
+fn bars(x: usize, y: usize, size: usize) -> Color {
+    let spacing = (size / 4).max(2);
+    let rail = (size / 16).max(1);
+    let bar = x % spacing < (spacing / 4).max(1);
+    let edge = y < rail || y >= size - rail;
+
+    if bar || edge { Color::new(60, 60, 70, 255) } else { Color::new(0, 0, 0, 0) }
+}
+
+// Pale tinted pane inside an opaque frame
+fn glass(x: usize, y: usize, size: usize) -> Color {
+    let frame = (size / 16).max(1);
+    if x < frame || y < frame || x >= size - frame || y >= size - frame {
+        Color::new(90, 70, 50, 255)
+    } else {
+        Color::new(170, 210, 230, 80)
+    }
+}
+
+// Vertical wooden boards with dark seams, held together by two braces
+fn planks(x: usize, y: usize, size: usize) -> Color {
+    let board = (size / 4).max(2);
+    let brace = (size / 16).max(1);
+    let seam = x.is_multiple_of(board);
+    let braced = (y + size / 2 - brace) % (size / 2).max(1) < brace * 2;
+
+    if braced {
+        Color::new(90, 60, 30, 255)
+    } else if seam {
+        Color::new(50, 30, 15, 255)
+    } else {
+        let grain = (hash(x / board, y / 3) % 25) as u8;
+        Color::new(120 + grain, 80 + grain / 2, 40, 255)
+    }
+}
+
+// Gold key lying on its side low in the image, for sprites; the rest is transparent
+fn key(x: usize, y: usize, size: usize) -> Color {
+    let fx = (x as f32 + 0.5) / size as f32;
+    let fy = (y as f32 + 0.5) / size as f32;
+    let ring = (fx - 0.3).hypot(fy - 0.7);
+
+    let head = (0.06..0.12).contains(&ring);
+    let shaft = (0.4..0.8).contains(&fx) && (0.68..0.73).contains(&fy);
+    let teeth =
+        ((0.62..0.67).contains(&fx) || (0.72..0.8).contains(&fx)) && (0.73..0.8).contains(&fy);
+
+    if head || shaft || teeth { Color::new(230, 190, 40, 255) } else { Color::new(0, 0, 0, 0) }
+}
+
+// Row of steel spikes standing on the floor, for sprites; the rest is transparent
+fn spikes(x: usize, y: usize, size: usize) -> Color {
+    const COUNT: f32 = 5.0;
+    let fx = (x as f32 + 0.5) / size as f32;
+    let fy = (y as f32 + 0.5) / size as f32;
+    // Each spike narrows from the floor up to a point 0.4 of the way up
+    let across = (fx * COUNT).fract() - 0.5;
+    let height = (1.0 - fy) / 0.4;
+
+    if height < 1.0 && across.abs() < 0.45 * (1.0 - height) {
+        let shade = if across < 0.0 { 200 } else { 140 };
+        Color::new(shade, shade, shade + 10, 255)
+    } else {
+        Color::new(0, 0, 0, 0)
+    }
+}
+
+// Glowing orange melt with darker crusted patches
+fn lava(x: usize, y: usize, size: usize) -> Color {
+    let patch = (size / 8).max(1);
+    let crust = hash(x / patch, y / patch).is_multiple_of(4);
+    let heat = (hash(x, y) % 60) as u8;
+
+    if crust {
+        Color::new(90 + heat / 2, 30, 10, 255)
+    } else {
+        Color::new(195 + heat, 80 + heat, 10, 255)
+    }
+}
+
+// Small integer hash so placeholders look the same on every run
+fn hash(x: usize, y: usize) -> u32 {
+    let mut h = (x as u32).wrapping_mul(374_761_393) ^ (y as u32).wrapping_mul(668_265_263);
+    h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+    h ^ (h >> 16)
+}
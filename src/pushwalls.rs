@@ -0,0 +1,294 @@
+// src/pushwalls.rs
+use crate::caster::{CellTrace, Face, SlabHit, box_hit};
+use crate::materials::MaterialRegistry;
+use crate::pathfinding::Cell;
+use crate::player::Player;
+use raylib::prelude::*;
+use std::collections::HashSet;
+
+// Glyph left behind where a pushwall used to stand
+const FLOOR_GLYPH: char = ' ';
+
+// A wall sliding from `from` towards `from + cells * step`
+struct Slide {
+    glyph: char,
+    from: Cell,
+    step: (i64, i64),
+    cells: usize,
+    progress: f32, // Cells travelled so far
+}
+
+impl Slide {
+    // World-space box the wall covers right now
+    fn bounds(&self, block_size: usize) -> (Vector2, Vector2) {
+        let block = block_size as f32;
+        let x = (self.from.0 as f32 + self.step.0 as f32 * self.progress) * block;
+        let y = (self.from.1 as f32 + self.step.1 as f32 * self.progress) * block;
+        (Vector2::new(x, y), Vector2::new(x + block, y + block))
+    }
+
+    // The one or two cells the wall overlaps
+    fn cells(&self) -> [Cell; 2] {
+        let cell = |travelled: f32| {
+            let travelled = travelled as i64;
+            (
+                (self.from.0 as i64 + self.step.0 * travelled) as usize,
+                (self.from.1 as i64 + self.step.1 * travelled) as usize,
+            )
+        };
+        [cell(self.progress.floor()), cell(self.progress.ceil())]
+    }
+
+    // Every cell the wall is in or still has to cross
+    fn path(&self) -> impl Iterator<Item = Cell> + '_ {
+        (self.progress.floor() as usize..=self.cells).map(|travelled| {
+            (
+                (self.from.0 as i64 + self.step.0 * travelled as i64) as usize,
+                (self.from.1 as i64 + self.step.1 * travelled as i64) as usize,
+            )
+        })
+    }
+}
+
+// Secret walls that slide back when the player pushes them. While a wall moves it is not
+// in the maze grid; collision and the renderer ask this instead. It is written back into
+// the grid where it stops, and can't be pushed again.
+pub struct Pushwalls {
+    hidden: HashSet<Cell>, // Pushwalls nobody has pushed yet
+    start: HashSet<Cell>,
+    sliding: Vec<Slide>,
+    block_size: usize,
+}
+
+impl Pushwalls {
+    const SPEED: f32 = 1.0; // Cells per second
+    const DISTANCE: usize = 2; // Cells a push moves the wall, unless something is in the way
+    const REACH: f32 = 1.5; // In blocks
+
+    pub fn new(maze: &[Vec<char>], materials: &MaterialRegistry, block_size: usize) -> Self {
+        let mut hidden = HashSet::new();
+        for (row, cells) in maze.iter().enumerate() {
+            for (col, &glyph) in cells.iter().enumerate() {
+                if materials.get(glyph).pushwall {
+                    hidden.insert((col, row));
+                }
+            }
+        }
+
+        Self { start: hidden.clone(), hidden, sliding: Vec::new(), block_size }
+    }
+
+    // The maze itself is restored by whoever owns it
+    pub fn reset(&mut self) {
+        self.hidden = self.start.clone();
+        self.sliding.clear();
+    }
+
+    pub fn total(&self) -> usize {
+        self.start.len()
+    }
+
+    // Pushwalls not pushed yet
+    pub fn hidden(&self) -> Vec<Cell> {
+        self.hidden.iter().copied().collect()
+    }
+
+    // Forget which walls have been pushed, as of a saved game. Only cells that started as
+    // pushwalls count.
+    pub fn restore(&mut self, hidden: &[Cell]) {
+        self.hidden = hidden.iter().copied().filter(|cell| self.start.contains(cell)).collect();
+        self.sliding.clear();
+    }
+
+    // The maze with every moving wall already at its destination
+    pub fn settled(&self, maze: &[Vec<char>]) -> Vec<Vec<char>> {
+        let mut maze = maze.to_vec();
+        for slide in &self.sliding {
+            let col = (slide.from.0 as i64 + slide.step.0 * slide.cells as i64) as usize;
+            let row = (slide.from.1 as i64 + slide.step.1 * slide.cells as i64) as usize;
+            maze[row][col] = slide.glyph;
+        }
+        maze
+    }
+
+    // Push the pushwall the player is facing, away from them. Returns whether it moved,
+    // which makes it a found secret.
+    pub fn push(
+        &mut self,
+        player: &Player,
+        maze: &mut [Vec<char>],
+        materials: &MaterialRegistry,
+    ) -> bool {
+        let reach = Self::REACH * self.block_size as f32;
+        let Some(crossing) = CellTrace::new(maze, player.pos, player.a, self.block_size, reach)
+            .find(|crossing| materials.blocks_sight(maze[crossing.row][crossing.col]))
+        else {
+            return false;
+        };
+        let from = (crossing.col, crossing.row);
+        if !self.hidden.contains(&from) {
+            return false;
+        }
+
+        // Entering through the west face means the player is pushing east, and so on
+        let step = match crossing.face {
+            Face::West => (1, 0),
+            Face::East => (-1, 0),
+            Face::North => (0, 1),
+            Face::South => (0, -1),
+        };
+        let player_cell =
+            (player.pos.x as usize / self.block_size, player.pos.y as usize / self.block_size);
+        let cells = (1..=Self::DISTANCE)
+            .take_while(|&travelled| {
+                let col = from.0 as i64 + step.0 * travelled as i64;
+                let row = from.1 as i64 + step.1 * travelled as i64;
+                self.is_free(col, row, maze, materials, player_cell)
+            })
+            .count();
+        if cells == 0 {
+            return false;
+        }
+
+        let glyph = maze[from.1][from.0];
+        maze[from.1][from.0] = FLOOR_GLYPH;
+        self.hidden.remove(&from);
+        self.sliding.push(Slide { glyph, from, step, cells, progress: 0.0 });
+        true
+    }
+
+    // Plain floor with nothing on it that a wall could cover: no pickups, checkpoints,
+    // hazards, start or goal
+    fn is_free(
+        &self,
+        col: i64,
+        row: i64,
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        player_cell: Cell,
+    ) -> bool {
+        let (Ok(col), Ok(row)) = (usize::try_from(col), usize::try_from(row)) else {
+            return false;
+        };
+        let Some(&glyph) = maze.get(row).and_then(|cells| cells.get(col)) else {
+            return false;
+        };
+        let material = materials.get(glyph);
+        material.walkable
+            && !material.solid
+            && !material.door
+            && !material.teleporter
+            && !material.checkpoint
+            && material.key.is_none()
+            && material.damage <= 0.0
+            && material.sprite.is_none()
+            && glyph != 'p'
+            && glyph != 'g'
+            && (col, row) != player_cell
+            && !self.claims(col, row)
+    }
+
+    // Slide moving walls along and set down the ones that have arrived
+    pub fn update(&mut self, dt: f32, maze: &mut [Vec<char>]) {
+        for slide in &mut self.sliding {
+            slide.progress = (slide.progress + Self::SPEED * dt).min(slide.cells as f32);
+        }
+        self.sliding.retain(|slide| {
+            if slide.progress < slide.cells as f32 {
+                return true;
+            }
+            let [_, (col, row)] = slide.cells();
+            maze[row][col] = slide.glyph;
+            false
+        });
+    }
+
+    // Whether a moving wall covers this point
+    pub fn is_blocked(&self, x: f32, y: f32) -> bool {
+        self.sliding.iter().any(|slide| {
+            let (min, max) = slide.bounds(self.block_size);
+            (min.x..max.x).contains(&x) && (min.y..max.y).contains(&y)
+        })
+    }
+
+    // Whether a moving wall is in the cell or will slide through it. The player can't
+    // step into these, so nobody gets walled in.
+    pub fn claims(&self, col: usize, row: usize) -> bool {
+        self.sliding.iter().any(|slide| slide.path().any(|cell| cell == (col, row)))
+    }
+
+    // Whether a moving wall overlaps the cell
+    pub fn occupies(&self, col: usize, row: usize) -> bool {
+        self.sliding.iter().any(|slide| slide.cells().contains(&(col, row)))
+    }
+
+    // Where a ray inside cell (col, row) meets a moving wall, and the wall's glyph
+    pub fn hit(
+        &self,
+        origin: Vector2,
+        a: f32,
+        col: usize,
+        row: usize,
+        enter: f32,
+        exit: f32,
+    ) -> Option<(SlabHit, char)> {
+        self.sliding
+            .iter()
+            .filter(|slide| slide.cells().contains(&(col, row)))
+            .filter_map(|slide| {
+                let (min, max) = slide.bounds(self.block_size);
+                box_hit(origin, a, min, max, enter, exit).map(|hit| (hit, slide.glyph))
+            })
+            .min_by(|a, b| a.0.distance.total_cmp(&b.0.distance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: usize = 64;
+
+    fn maze(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    // Push the wall east of the start, facing east
+    fn push(rows: &[&str]) -> (Pushwalls, Vec<Vec<char>>, bool) {
+        let materials = MaterialRegistry::builtin();
+        let mut maze = maze(rows);
+        let mut player = Player::from_maze(&maze, BLOCK).expect("maze has a start");
+        player.a = 0.0;
+        let mut pushwalls = Pushwalls::new(&maze, &materials, BLOCK);
+        let moved = pushwalls.push(&player, &mut maze, &materials);
+        (pushwalls, maze, moved)
+    }
+
+    #[test]
+    fn sliding_wall_claims_its_destination() {
+        let (mut pushwalls, mut maze, moved) = push(&["######", "#p%  #", "######"]);
+        assert!(moved);
+        assert!(pushwalls.claims(4, 1));
+
+        pushwalls.update(0.5, &mut maze);
+        assert!(pushwalls.claims(4, 1));
+        pushwalls.update(2.0, &mut maze);
+        assert!(!pushwalls.claims(4, 1));
+        assert_eq!(maze[1][4], '%');
+    }
+
+    #[test]
+    fn wall_stops_before_a_checkpoint() {
+        let (pushwalls, _, moved) = push(&["######", "#p% c#", "######"]);
+        assert!(moved);
+        assert!(pushwalls.claims(3, 1));
+        assert!(!pushwalls.claims(4, 1));
+    }
+
+    #[test]
+    fn wall_is_not_pushed_onto_the_goal() {
+        let (_, maze, moved) = push(&["#####", "#p%g#", "#####"]);
+        assert!(!moved);
+        assert_eq!(maze[1][2], '%');
+    }
+}
@@ -0,0 +1,167 @@
+// src/records.rs
+use crate::json_file::{self, WriteError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// One finished run on a level's board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub name: String,
+    pub time: f32,    // Seconds
+    pub date: String, // YYYY-MM-DD, UTC
+    #[serde(default)]
+    pub splits: Vec<f32>, // Checkpoint splits then the finish, for comparing later runs
+}
+
+// Fastest runs for every level played, keyed by a hash of the maze so an edited maze
+// starts a fresh board
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Records {
+    levels: BTreeMap<String, Vec<Record>>,
+}
+
+impl Records {
+    pub const FILE_NAME: &'static str = "records.json";
+    pub const KEEP: usize = 10; // Runs kept per level
+
+    pub fn load(path: &Path) -> Self {
+        json_file::load_or_default(path, "records")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), WriteError> {
+        json_file::save(path, self)
+    }
+
+    // Best first
+    pub fn top(&self, level: &str) -> &[Record] {
+        self.levels.get(level).map_or(&[], Vec::as_slice)
+    }
+
+    // Add a finished run. Returns its place on the board (0 is a new record), or None if it
+    // was too slow to be kept.
+    pub fn add(&mut self, level: &str, record: Record) -> Option<usize> {
+        let board = self.levels.entry(level.to_string()).or_default();
+        // Ties go to whoever got there first
+        let place = board.partition_point(|kept| kept.time <= record.time);
+        if place >= Self::KEEP {
+            return None;
+        }
+        board.insert(place, record);
+        board.truncate(Self::KEEP);
+        Some(place)
+    }
+}
+
+// Stable hash of the maze layout (64-bit FNV-1a), as hex
+pub fn level_key(maze: &Vec<Vec<char>>) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for row in maze {
+        for &glyph in row.iter().chain(&['\n']) {
+            for byte in glyph.to_string().bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+// Today's date in UTC as YYYY-MM-DD
+pub fn today() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Days since 1970-01-01 to a Gregorian date (Howard Hinnant's algorithm)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153; // March is 0
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn run(name: &str, time: f32) -> Record {
+        Record { name: name.to_string(), time, date: "2026-01-01".to_string(), splits: Vec::new() }
+    }
+
+    fn names(records: &Records, level: &str) -> Vec<String> {
+        records.top(level).iter().map(|record| record.name.clone()).collect()
+    }
+
+    #[test]
+    fn runs_are_kept_fastest_first() {
+        let mut records = Records::default();
+        assert_eq!(records.add("level", run("slow", 30.0)), Some(0));
+        assert_eq!(records.add("level", run("fast", 10.0)), Some(0));
+        assert_eq!(records.add("level", run("middle", 20.0)), Some(1));
+        assert_eq!(names(&records, "level"), ["fast", "middle", "slow"]);
+    }
+
+    #[test]
+    fn ties_go_to_the_earlier_run() {
+        let mut records = Records::default();
+        records.add("level", run("first", 15.0));
+        assert_eq!(records.add("level", run("second", 15.0)), Some(1));
+        assert_eq!(names(&records, "level"), ["first", "second"]);
+    }
+
+    #[test]
+    fn board_is_cut_at_keep() {
+        let mut records = Records::default();
+        for place in 0..Records::KEEP {
+            records.add("level", run(&place.to_string(), 10.0 + place as f32));
+        }
+        assert_eq!(records.add("level", run("too slow", 100.0)), None);
+        assert_eq!(records.add("level", run("fastest", 1.0)), Some(0));
+
+        let board = records.top("level");
+        assert_eq!(board.len(), Records::KEEP);
+        assert_eq!(board.last().map(|record| record.name.as_str()), Some("8"));
+    }
+
+    #[test]
+    fn levels_have_separate_boards() {
+        let mut records = Records::default();
+        records.add("a", run("on a", 10.0));
+        assert_eq!(records.add("b", run("on b", 20.0)), Some(0));
+        assert!(records.top("c").is_empty());
+    }
+
+    #[test]
+    fn known_days_convert_to_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+    }
+
+    #[test]
+    fn level_key_follows_the_layout() {
+        let maze = |rows: &[&str]| -> Vec<Vec<char>> {
+            rows.iter().map(|row| row.chars().collect()).collect()
+        };
+        let level = maze(&["####", "#pg#", "####"]);
+
+        assert_eq!(level_key(&level), level_key(&maze(&["####", "#pg#", "####"])));
+        assert_ne!(level_key(&level), level_key(&maze(&["####", "#p g", "####"])));
+        assert_ne!(level_key(&level), level_key(&maze(&["####", "#gp#", "####"])));
+        // Rows are kept apart, so moving a glyph across a row break changes the key
+        assert_ne!(level_key(&maze(&["ab", "c"])), level_key(&maze(&["a", "bc"])));
+    }
+}
@@ -1,114 +1,718 @@
 // src/render3d.rs
-use crate::caster::cast_ray;
+use crate::caster::{CellTrace, Face};
+use crate::doors::Doors;
+use crate::exploration::ExplorationMap;
+use crate::fog::Fog;
 use crate::framebuffer::Framebuffer;
+use crate::inventory::Inventory;
+use crate::lighting::{Lighting, apply_light};
+use crate::materials::MaterialRegistry;
+use crate::pathfinding::Cell;
 use crate::player::Player;
-use crate::resources::Textures;
+use crate::pushwalls::Pushwalls;
+use crate::resources::{Texture, Textures};
+use crate::waypoints::{WAYPOINT_COLOR, Waypoints};
 use raylib::prelude::*;
+use std::f32::consts::PI;
+use std::ops::Range;
+use std::thread;
 
-pub fn render3d(
-    framebuffer: &mut Framebuffer,
-    player: &Player,
-    maze: &Vec<Vec<char>>,
-    textures: &Textures,
-) {
-    let block_size = 64;
-    let num_rays = framebuffer.width;
-
-    let hw = framebuffer.width as f32 / 2.0;
-    let hh = framebuffer.height as f32 / 2.0;
+// Everything the 3D view shows, shared read-only between the render threads
+pub struct World<'a> {
+    pub player: &'a Player,
+    pub maze: &'a Vec<Vec<char>>,
+    pub materials: &'a MaterialRegistry,
+    pub textures: &'a Textures,
+    pub lighting: &'a Lighting,
+    pub fog: &'a Fog,
+    pub doors: &'a Doors,
+    pub inventory: &'a Inventory, // Collected keys are no longer drawn
+    pub pushwalls: &'a Pushwalls,
+    pub time: f32,
+}
 
-    for i in (0..num_rays).step_by(2) {
-        let current_ray = i as f32 / num_rays as f32;
-        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-        let intersect = cast_ray(framebuffer, &maze, player, a, block_size, false);
+// A world plus the projection for one framebuffer
+struct ColumnRenderer<'a> {
+    world: &'a World<'a>,
+    width: u32,
+    height: u32,
+    projection: f32,
+    horizon: f32,
+}
 
-        if intersect.distance < 1.0 {
-            continue;
+// The 3D view. The thread count is settled when it is made, and the scratch image and
+// seen lists are kept between frames so drawing one allocates nothing.
+pub struct Renderer3d {
+    threads: usize,
+    columns: Vec<Color>, // Column-major image, so every band is one contiguous slice
+    seen: Vec<Vec<Cell>>, // Cells each band's rays passed through this frame
+}
+
+impl Renderer3d {
+    pub fn new(threads: usize) -> Self {
+        let threads = threads.max(1);
+        Self { threads, columns: Vec::new(), seen: vec![Vec::new(); threads] }
+    }
+
+    // Casts one ray per column. Columns are split into bands, one per thread; each band
+    // writes only its own columns, so the image is the same whatever the thread count.
+    // The rows are then copied back into the framebuffer in bands too.
+    pub fn render(
+        &mut self,
+        framebuffer: &mut Framebuffer,
+        world: &World,
+        exploration: &mut ExplorationMap,
+    ) {
+        let width = framebuffer.width as usize;
+        let height = framebuffer.height as usize;
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let renderer = ColumnRenderer::new(world, framebuffer);
+        self.columns.clear();
+        self.columns.resize(width * height, framebuffer.background_color);
+        let band_width = width.div_ceil(self.threads.min(width));
+        let band_height = height.div_ceil(self.threads.min(height));
+
+        thread::scope(|scope| {
+            let renderer = &renderer;
+            let bands = self.columns.chunks_mut(band_width * height).zip(&mut self.seen);
+            for (band, (slice, seen)) in bands.enumerate() {
+                scope.spawn(move || {
+                    seen.clear();
+                    for (offset, column) in slice.chunks_mut(height).enumerate() {
+                        renderer.render_column(band * band_width + offset, column, seen);
+                    }
+                });
+            }
+        });
+
+        // Back into the framebuffer's row-major layout
+        let columns = &self.columns;
+        thread::scope(|scope| {
+            for (band, rows) in framebuffer.buffer.chunks_mut(band_height * width).enumerate() {
+                scope.spawn(move || {
+                    for (offset, row) in rows.chunks_mut(width).enumerate() {
+                        let y = band * band_height + offset;
+                        for (x, pixel) in row.iter_mut().enumerate() {
+                            *pixel = columns[x * height + y];
+                        }
+                    }
+                });
+            }
+        });
+
+        for &(col, row) in self.seen.iter().flatten() {
+            exploration.mark(col, row);
+        }
+    }
+}
+
+impl<'a> ColumnRenderer<'a> {
+    fn new(world: &'a World<'a>, framebuffer: &Framebuffer) -> Self {
+        Self {
+            world,
+            width: framebuffer.width,
+            height: framebuffer.height,
+            projection: projection_distance(world.player, framebuffer.width as f32),
+            horizon: horizon(world.player, framebuffer.height as f32),
+        }
+    }
+
+    // Walks the ray front to back through the heightmap. Each cell contributes a rising
+    // face where it is taller than the cell before it, then its top surface and ceiling.
+    // `clip` holds the rows nothing nearer has covered yet; the walk ends once it is empty.
+    // See-through walls don't cover anything, so they are queued and blended in last.
+    // Doors are slabs partway through their cell, so the cell's floor stops at the door.
+    fn render_column(&self, i: usize, column: &mut [Color], seen: &mut Vec<Cell>) {
+        const MAX_DISTANCE: f32 = 1000.0;
+
+        let World { player, maze, materials, .. } = *self.world;
+
+        // Ray through the centre of this column on the projection plane
+        let offset = i as f32 + 0.5 - self.width as f32 / 2.0;
+        let angle_diff = (offset / self.projection).atan();
+        let ray = Ray { a: player.a + angle_diff, angle_diff, eye: player.eye_height() };
+
+        let col = player.pos.x as usize / BLOCK_SIZE;
+        let row = player.pos.y as usize / BLOCK_SIZE;
+        let Some(&start) = maze.get(row).and_then(|cells| cells.get(col)) else {
+            return;
+        };
+
+        let mut clip = 0..self.height as usize;
+        let mut cell = (col, row, start);
+        let mut enter = 0.0;
+        let mut trace = CellTrace::new(maze, player.pos, ray.a, BLOCK_SIZE, MAX_DISTANCE);
+        let mut see_through = Vec::new();
+
+        loop {
+            let crossing = trace.next();
+            let exit = crossing.as_ref().map_or(MAX_DISTANCE, |crossing| crossing.distance);
+
+            let (col, row, glyph) = cell;
+            let partial = self.partial_hit(&ray, cell, enter, exit);
+            let floor_end = partial.as_ref().map_or(exit, |(_, face)| face.distance);
+            if self.draw_surfaces(column, &mut clip, &ray, glyph, enter, floor_end) {
+                seen.push((col, row));
+            }
+            if let Some((glyph, face)) = partial
+                && self.draw_face(column, &mut clip, &ray, glyph, &face)
+            {
+                seen.push((col, row));
+            }
+
+            let Some(crossing) = crossing else {
+                break;
+            };
+            if clip.is_empty() {
+                break;
+            }
+
+            let next = maze[crossing.row][crossing.col];
+            let (bottom, top) = (materials.get(glyph).top(), materials.get(next).top());
+            let face = WallFace {
+                distance: crossing.distance,
+                face: crossing.face,
+                u: self.face_u(&ray, crossing.distance, crossing.face),
+                bottom,
+                top,
+            };
+            if top > bottom && self.draw_face(column, &mut clip, &ray, next, &face) {
+                seen.push((crossing.col, crossing.row));
+            }
+
+            let material = materials.get(next);
+            if material.solid && material.transparent {
+                let face = WallFace {
+                    bottom: material.elevation,
+                    top: material.elevation + material.height,
+                    ..face
+                };
+                see_through.push((crossing.col, crossing.row, next, face, clip.clone()));
+            }
+
+            cell = (crossing.col, crossing.row, next);
+            enter = crossing.distance;
+        }
+
+        // Farthest first, each over whatever was visible through it when it was passed
+        for (col, row, glyph, face, mut clip) in see_through.into_iter().rev() {
+            if self.draw_face(column, &mut clip, &ray, glyph, &face) {
+                seen.push((col, row));
+            }
         }
+    }
+
+    // Texture U where the ray meets a cell boundary at `distance`
+    fn face_u(&self, ray: &Ray, distance: f32, face: Face) -> f32 {
+        let player = self.world.player;
+        let hit_x = player.pos.x + distance * ray.a.cos();
+        let hit_y = player.pos.y + distance * ray.a.sin();
+        face_u(face, hit_x, hit_y, BLOCK_SIZE)
+    }
+
+    // Doors and sliding walls fill only part of their cell. Where the ray meets one inside
+    // `cell` between `enter` and `exit`, and the glyph to draw it with.
+    fn partial_hit(
+        &self,
+        ray: &Ray,
+        (col, row, glyph): (usize, usize, char),
+        enter: f32,
+        exit: f32,
+    ) -> Option<(char, WallFace)> {
+        let World { player, materials, doors, pushwalls, .. } = *self.world;
+
+        // A closed door covers its cell's full height from the floor up
+        let (glyph, hit, u) = match doors.hit(player.pos, ray.a, col, row, enter, exit) {
+            Some((hit, u)) => (glyph, hit, u),
+            None => {
+                let (hit, glyph) = pushwalls.hit(player.pos, ray.a, col, row, enter, exit)?;
+                let u = mirror_u(hit.face, hit.offset);
+                (glyph, hit, u)
+            }
+        };
+        let material = materials.get(glyph);
+        let face = WallFace {
+            distance: hit.distance,
+            face: hit.face,
+            u,
+            bottom: material.elevation,
+            top: material.elevation + material.height,
+        };
+        Some((glyph, face))
+    }
+
+    // First screen row hidden by something nearer than `limit` along the ray: wall faces,
+    // the far rims of cells, closed doors and sliding walls. Everything from that row down
+    // is covered.
+    fn covered_from(&self, ray: &Ray, limit: f32) -> f32 {
+        let World { player, maze, materials, .. } = *self.world;
+        let cos_diff = ray.angle_diff.cos();
 
-        // Correct fisheye effect
-        let angle_diff = a - player.a;
-        let corrected_distance = intersect.distance * angle_diff.cos();
-
-        // Calculate stake height
-        let stake_height = (hh * 100.0) / corrected_distance;
-        let stake_height = stake_height.min(framebuffer.height as f32);
-
-        // Calculate vertical positions
-        let stake_top = ((hh - (stake_height / 2.0)).max(0.0)) as usize;
-        let stake_bottom = ((hh + (stake_height / 2.0)).min(framebuffer.height as f32)) as usize;
-
-        // Special rendering for goal tile - make it glow with pulsing effect
-        if intersect.impact == 'g' {
-            // Make goal walls golden/yellow
-            let goal_color = Color::new(255, 215, 0, 255); // Gold color
-
-            for y in stake_top..stake_bottom {
-                if y < framebuffer.height as usize {
-                    // Apply pulsing effect
-                    let pulse = ((y as f32 * 0.1).sin() * 0.5 + 0.5);
-                    let shaded_color = Color::new(
-                        (goal_color.r as f32 * pulse) as u8,
-                        (goal_color.g as f32 * pulse) as u8,
-                        goal_color.b,
-                        255,
-                    );
-
-                    framebuffer.set_current_color(shaded_color);
-                    framebuffer.set_pixel(i as u32, y as u32);
-                    if (i + 1) as u32 <= framebuffer.width - 1 {
-                        framebuffer.set_pixel((i + 1) as u32, y as u32);
+        let col = player.pos.x as usize / BLOCK_SIZE;
+        let row = player.pos.y as usize / BLOCK_SIZE;
+        let Some(&start) = maze.get(row).and_then(|cells| cells.get(col)) else {
+            return 0.0;
+        };
+
+        let mut covered = self.height as f32;
+        let mut cell = (col, row, start);
+        let mut enter = 0.0;
+        let mut trace = CellTrace::new(maze, player.pos, ray.a, BLOCK_SIZE, limit);
+
+        loop {
+            let crossing = trace.next();
+            let exit = crossing.as_ref().map_or(limit, |crossing| crossing.distance);
+
+            if let Some((_, face)) = self.partial_hit(ray, cell, enter, exit) {
+                covered = covered.min(self.row_at(face.top, ray.eye, face.distance * cos_diff));
+            }
+
+            let Some(crossing) = crossing else {
+                return covered;
+            };
+
+            // Whichever side of the boundary is higher hides what is below it
+            let next = maze[crossing.row][crossing.col];
+            let top = materials.get(cell.2).top().max(materials.get(next).top());
+            covered = covered.min(self.row_at(top, ray.eye, crossing.distance * cos_diff));
+
+            cell = (crossing.col, crossing.row, next);
+            enter = crossing.distance;
+        }
+    }
+
+    // Screen row where height `z` (in blocks) appears at perpendicular distance `distance`
+    fn row_at(&self, z: f32, eye: f32, distance: f32) -> f32 {
+        self.horizon - (z - eye) * BLOCK_SIZE as f32 * self.projection / distance
+    }
+
+    // Rows whose centres fall in [top, bottom), limited to the still uncovered rows
+    fn rows(top: f32, bottom: f32, clip: &Range<usize>) -> Range<usize> {
+        let first = ((top - 0.5).ceil().max(0.0) as usize).max(clip.start);
+        let last = ((bottom - 0.5).ceil().max(0.0) as usize).min(clip.end);
+        first..last.max(first)
+    }
+
+    // Vertical face of `glyph` hit by the ray. Opaque faces cover the rows below them;
+    // see-through ones are blended by texel alpha. Returns whether any of it was visible.
+    fn draw_face(
+        &self,
+        column: &mut [Color],
+        clip: &mut Range<usize>,
+        ray: &Ray,
+        glyph: char,
+        face: &WallFace,
+    ) -> bool {
+        let World { player, lighting, textures, fog, .. } = *self.world;
+        let block_pixels = BLOCK_SIZE as f32 * self.projection;
+        let distance = face.distance * ray.angle_diff.cos();
+        let (bottom, top) = (face.bottom, face.top);
+
+        let rows = Self::rows(
+            self.row_at(top, ray.eye, distance),
+            self.row_at(bottom, ray.eye, distance),
+            clip,
+        );
+        if rows.is_empty() {
+            return false;
+        }
+
+        // Light the face from the open cell just in front of it. East and west faces are
+        // drawn darker so corners read clearly.
+        let lit_distance = (face.distance - 1.0).max(0.0);
+        let light = lighting.light_surface(
+            player.pos.x + lit_distance * ray.a.cos(),
+            player.pos.y + lit_distance * ray.a.sin(),
+            face.distance,
+            ray.angle_diff,
+        );
+        let side = if face.face.is_east_west() { EAST_WEST_SHADE } else { 1.0 };
+        let light = light.map(|channel| channel * side);
+
+        // Walkable steps fall back to their floor texture; anything untextured is drawn in
+        // its map colour
+        let material = self.world.materials.get(glyph);
+        let texture = textures
+            .get_wall_texture(material, self.world.time)
+            .or_else(|| textures.get_floor_texture(material));
+        let flat_color = material.minimap_color().unwrap_or(Color::MAGENTA);
+
+        let u = face.u;
+        let lod = texture.map_or(0.0, |texture| texture_lod(distance, texture, block_pixels));
+
+        for y in rows.clone() {
+            let color = if glyph == 'g' {
+                // Goal walls glow gold with a pulsing effect
+                let pulse = (y as f32 * 0.1).sin() * 0.5 + 0.5;
+                Color::new((255.0 * pulse) as u8, (215.0 * pulse) as u8, 0, 255)
+            } else {
+                match texture {
+                    Some(texture) => {
+                        // V runs down from the top of the face and repeats every block, so
+                        // tall walls tile instead of stretching
+                        let z =
+                            ray.eye + (self.horizon - (y as f32 + 0.5)) * distance / block_pixels;
+                        textures.sample(texture, u, (top - z).rem_euclid(1.0), lod)
                     }
+                    None => flat_color,
                 }
+            };
+            let color = fog.apply(apply_light(color, light), distance);
+            column[y] = if material.transparent { blend(column[y], color) } else { color };
+        }
+
+        // Faces stand on what is in front of them, so everything below is now covered
+        if !material.transparent {
+            clip.end = clip.end.min(rows.start);
+        }
+        true
+    }
+
+    // Top surface and ceiling of `glyph`'s cell between ray distances `enter` and `exit`.
+    // Returns whether any of it was visible.
+    fn draw_surfaces(
+        &self,
+        column: &mut [Color],
+        clip: &mut Range<usize>,
+        ray: &Ray,
+        glyph: char,
+        enter: f32,
+        exit: f32,
+    ) -> bool {
+        let textures = self.world.textures;
+        let material = self.world.materials.get(glyph);
+        let cos_diff = ray.angle_diff.cos();
+        let (near, far) = (enter * cos_diff, exit * cos_diff);
+        let mut visible = false;
+
+        // Floors, and the tops of walls low enough to look down on
+        let top = material.top();
+        if top < ray.eye {
+            let near_row = if near > 0.0 { self.row_at(top, ray.eye, near) } else { f32::INFINITY };
+            let rows = Self::rows(self.row_at(top, ray.eye, far), near_row, clip);
+            let texture = textures
+                .get_floor_texture(material)
+                .or_else(|| textures.get_wall_texture(material, self.world.time));
+            let flat_color = material.solid.then(|| material.minimap_color()).flatten();
+
+            for y in rows.clone() {
+                let Some(color) =
+                    self.sample_plane(texture, flat_color, ray, top, y, material.teleporter)
+                else {
+                    continue;
+                };
+                column[y] = color;
+            }
+            if !rows.is_empty() {
+                clip.end = rows.start;
+                visible = true;
+            }
+        }
+
+        // Ceilings sit at a fixed height over open cells that have one
+        let ceiling = textures.get_ceiling_texture(material);
+        if !material.solid && ceiling.is_some() && CEILING_HEIGHT > ray.eye {
+            let near_row = if near > 0.0 {
+                self.row_at(CEILING_HEIGHT, ray.eye, near)
+            } else {
+                f32::NEG_INFINITY
+            };
+            let rows = Self::rows(near_row, self.row_at(CEILING_HEIGHT, ray.eye, far), clip);
+
+            for y in rows.clone() {
+                if let Some(color) = self.sample_plane(ceiling, None, ray, CEILING_HEIGHT, y, false)
+                {
+                    column[y] = color;
+                }
+            }
+            if !rows.is_empty() {
+                clip.start = rows.end;
+                visible = true;
             }
-            continue;
         }
 
-        // Get the appropriate texture
-        let texture = textures.get_wall_texture(intersect.impact);
+        visible
+    }
 
-        // Calculate texture coordinate
-        let hit_x = player.pos.x + intersect.distance * a.cos();
-        let hit_y = player.pos.y + intersect.distance * a.sin();
+    // Colour of a horizontal plane at height `z` seen through screen row `y`. `glow` adds
+    // the teleporter pad effect on top of the lit surface.
+    fn sample_plane(
+        &self,
+        texture: Option<&Texture>,
+        flat_color: Option<Color>,
+        ray: &Ray,
+        z: f32,
+        y: usize,
+        glow: bool,
+    ) -> Option<Color> {
+        let block = BLOCK_SIZE as f32;
+        let block_pixels = block * self.projection;
+
+        // Perpendicular distance to where this row meets the plane
+        let row_distance = (ray.eye - z) * block_pixels / (y as f32 + 0.5 - self.horizon);
+        let ray_distance = row_distance / ray.angle_diff.cos();
+        if !ray_distance.is_finite() || ray_distance < 0.0 {
+            return None;
+        }
+        let World { player, textures, lighting, fog, .. } = *self.world;
+        let x = player.pos.x + ray_distance * ray.a.cos();
+        let plane_y = player.pos.y + ray_distance * ray.a.sin();
 
-        let tex_coord_x = match intersect.impact {
-            '|' => (hit_y % block_size as f32) / block_size as f32,
-            '-' | '+' | '#' | '*' => (hit_x % block_size as f32) / block_size as f32,
-            _ => (hit_x % block_size as f32) / block_size as f32,
+        let color = match texture {
+            Some(texture) => {
+                let u = x.rem_euclid(block) / block;
+                let v = plane_y.rem_euclid(block) / block;
+                let lod = texture_lod(row_distance, texture, block_pixels);
+                textures.sample(texture, u, v, lod)
+            }
+            None => flat_color?,
         };
+        let light = lighting.light_surface(x, plane_y, ray_distance, ray.angle_diff);
+        let mut color = apply_light(color, light);
+        if glow {
+            color = add_glow(color, teleport_glow(x, plane_y, self.world.time));
+        }
+        Some(fog.apply(color, row_distance))
+    }
+}
+
+// One column's ray
+struct Ray {
+    a: f32,
+    angle_diff: f32, // Relative to the view direction
+    eye: f32,        // Eye height in blocks
+}
+
+// Where a ray meets a vertical face, and the heights in blocks the face spans
+#[derive(Clone, Copy)]
+struct WallFace {
+    distance: f32, // Along the ray
+    face: Face,
+    u: f32, // Texture U at the hit
+    bottom: f32,
+    top: f32,
+}
+
+const BLOCK_SIZE: usize = 64;
+const CEILING_HEIGHT: f32 = 1.0; // In blocks
+
+const EAST_WEST_SHADE: f32 = 0.7;
+const TELEPORT_COLOR: Color = Color::new(80, 200, 255, 255);
+
+// `over` composited onto `under` by its alpha
+fn blend(under: Color, over: Color) -> Color {
+    let alpha = over.a as f32 / 255.0;
+    let mix = |below: u8, above: u8| (below as f32 + (above as f32 - below as f32) * alpha) as u8;
+    Color::new(mix(under.r, over.r), mix(under.g, over.g), mix(under.b, over.b), 255)
+}
+
+// Rings of light shrinking into the middle of a teleporter pad, 0 to 1
+fn teleport_glow(x: f32, y: f32, time: f32) -> f32 {
+    let block = BLOCK_SIZE as f32;
+    let dx = x.rem_euclid(block) / block - 0.5;
+    let dy = y.rem_euclid(block) / block - 0.5;
+    let r = dx.hypot(dy) * 2.0; // 1 at the middle of each edge
+    if r >= 1.0 {
+        return 0.0;
+    }
+    let rings = ((r * 3.0 + time * 1.5) * 2.0 * PI).sin() * 0.5 + 0.5;
+    rings.powi(3) * (1.0 - r)
+}
+
+// Teleporter light added on top of a surface, unaffected by the level lighting
+fn add_glow(color: Color, amount: f32) -> Color {
+    let add = |channel: u8, glow: u8| (channel as f32 + glow as f32 * amount).min(255.0) as u8;
+    Color::new(
+        add(color.r, TELEPORT_COLOR.r),
+        add(color.g, TELEPORT_COLOR.g),
+        add(color.b, TELEPORT_COLOR.b),
+        color.a,
+    )
+}
+
+// Texture U across the face that was hit, mirrored so every face reads left to right
+// when you stand in front of it
+fn face_u(face: Face, hit_x: f32, hit_y: f32, block_size: usize) -> f32 {
+    let block = block_size as f32;
+    let along = if face.is_east_west() { hit_y } else { hit_x };
+    mirror_u(face, along.rem_euclid(block) / block)
+}
+
+// `along` is the hit's position across the face from its low x or y edge
+fn mirror_u(face: Face, along: f32) -> f32 {
+    match face {
+        Face::North | Face::East => 1.0 - along,
+        Face::South | Face::West => along,
+    }
+}
+
+// Distance in pixels from the eye to the projection plane for the player's field of view
+fn projection_distance(player: &Player, width: f32) -> f32 {
+    (width / 2.0) / (player.fov / 2.0).tan()
+}
 
-        // Draw the column
-        for y in stake_top..stake_bottom {
-            if y < framebuffer.height as usize {
-                // Calculate texture Y coordinate
-                let tex_coord_y = (y - stake_top) as f32 / (stake_bottom - stake_top) as f32;
-
-                // Sample the texture
-                let tex_x = (tex_coord_x * textures.texture_size as f32) as usize;
-                let tex_y = (tex_coord_y * textures.texture_size as f32) as usize;
-
-                // Get pixel color from texture
-                let color = textures.get_texture_pixel(
-                    texture,
-                    tex_x.min(textures.texture_size - 1),
-                    tex_y.min(textures.texture_size - 1),
-                );
-
-                // Apply distance shading
-                let intensity = 1.0 - (corrected_distance / 500.0).min(0.8);
-                let shaded_color = Color::new(
-                    (color.r as f32 * intensity) as u8,
-                    (color.g as f32 * intensity) as u8,
-                    (color.b as f32 * intensity) as u8,
-                    255,
-                );
-
-                framebuffer.set_current_color(shaded_color);
-                framebuffer.set_pixel(i as u32, y as u32);
-                if (i + 1) as u32 <= framebuffer.width - 1 {
-                    framebuffer.set_pixel((i + 1) as u32, y as u32);
+// Screen row of the horizon. Looking up or down shears the view vertically.
+fn horizon(player: &Player, height: f32) -> f32 {
+    let hh = height / 2.0;
+    hh + player.pitch * hh
+}
+
+// Angle wrapped to [-PI, PI]
+fn wrap_angle(mut angle: f32) -> f32 {
+    while angle > PI {
+        angle -= 2.0 * PI;
+    }
+    while angle < -PI {
+        angle += 2.0 * PI;
+    }
+    angle
+}
+
+// log2 of texels per screen pixel for a surface at `distance`, where a block spans
+// `block_pixels / distance` pixels on screen
+fn texture_lod(distance: f32, texture: &Texture, block_pixels: f32) -> f32 {
+    let texels = texture.width().max(texture.height()) as f32;
+    (distance * texels / block_pixels).max(1.0).log2()
+}
+
+// Draw billboard sprites standing in their cells, farthest first. Each screen column of a
+// sprite stops where something nearer covers it, so sprites hide behind walls, closed doors
+// and the rims of low walls. Keys that have been picked up are skipped.
+pub fn render_sprites(framebuffer: &mut Framebuffer, world: &World) {
+    let World { player, maze, materials, textures, lighting, fog, inventory, .. } = *world;
+    let renderer = ColumnRenderer::new(world, framebuffer);
+    let block = BLOCK_SIZE as f32;
+    let block_pixels = block * renderer.projection;
+    let (width, height) = (framebuffer.width as usize, framebuffer.height as usize);
+    let eye = player.eye_height();
+
+    let mut sprites = Vec::new();
+    for (row, cells) in maze.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            let material = materials.get(cell);
+            if let Some(texture) = textures.get_sprite_texture(material)
+                && !inventory.is_collected(col, row)
+            {
+                let x = (col as f32 + 0.5) * block;
+                let y = (row as f32 + 0.5) * block;
+                let distance = (x - player.pos.x).hypot(y - player.pos.y);
+                sprites.push((distance, x, y, material, texture));
+            }
+        }
+    }
+    sprites.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    for (distance, x, y, material, texture) in sprites {
+        let angle_diff = wrap_angle((y - player.pos.y).atan2(x - player.pos.x) - player.a);
+        let depth = distance * angle_diff.cos();
+        if depth < 1.0 {
+            continue;
+        }
+
+        let size = block_pixels / depth;
+        let left = width as f32 / 2.0 + angle_diff.tan() * renderer.projection - size / 2.0;
+        let top = renderer.row_at(material.elevation + 1.0, eye, depth);
+        let bottom = renderer.row_at(material.elevation, eye, depth);
+
+        let light = lighting.light_surface(x, y, distance, angle_diff);
+        let lod = texture_lod(depth, texture, block_pixels);
+        let first = ((left - 0.5).ceil().max(0.0) as usize).min(width);
+        let last = ((left + size - 0.5).ceil().max(0.0) as usize).min(width);
+
+        for screen_x in first..last {
+            let column_diff =
+                ((screen_x as f32 + 0.5 - width as f32 / 2.0) / renderer.projection).atan();
+            let ray = Ray { a: player.a + column_diff, angle_diff: column_diff, eye };
+            let covered = renderer.covered_from(&ray, depth / column_diff.cos());
+            let u = (screen_x as f32 + 0.5 - left) / size;
+
+            for screen_y in ColumnRenderer::rows(top, bottom.min(covered), &(0..height)) {
+                let v = (screen_y as f32 + 0.5 - top) / (bottom - top);
+                let texel = textures.sample(texture, u, v, lod);
+                if texel.a == 0 {
+                    continue;
                 }
+                let color = fog.apply(apply_light(texel, light), depth);
+                let pixel = &mut framebuffer.buffer[screen_y * width + screen_x];
+                *pixel = blend(*pixel, color);
             }
         }
     }
 }
+
+// Draw waypoint markers floating at eye level. Returns (number, x, y) screen positions
+// for labels, which are drawn with raylib text afterwards.
+pub fn render_waypoints(
+    framebuffer: &mut Framebuffer,
+    world: &World,
+    waypoints: &Waypoints,
+) -> Vec<(usize, i32, i32)> {
+    let World { player, maze, materials, lighting, fog, doors, pushwalls, .. } = *world;
+    let hh = framebuffer.height as f32 / 2.0;
+    let projection = projection_distance(player, framebuffer.width as f32);
+    let horizon = horizon(player, framebuffer.height as f32);
+    let mut labels = Vec::new();
+
+    for (number, x, y) in waypoints.iter() {
+        let dx = x - player.pos.x;
+        let dy = y - player.pos.y;
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance < 1.0 {
+            continue;
+        }
+
+        // Angle relative to the view direction
+        let a = dy.atan2(dx);
+        let angle_diff = wrap_angle(a - player.a);
+        if angle_diff.abs() > player.fov / 2.0 {
+            continue;
+        }
+
+        // Hidden behind anything at least as tall as the eye, a door that isn't open or a
+        // sliding wall; low walls can be seen over
+        let eye = player.eye_height();
+        let hidden = CellTrace::new(maze, player.pos, a, BLOCK_SIZE, distance).any(|crossing| {
+            let (col, row) = (crossing.col, crossing.row);
+            materials.get(maze[row][col]).top() >= eye
+                || doors.get(col, row).is_some_and(|door| !door.is_passable())
+                || pushwalls.occupies(col, row)
+        });
+        if hidden {
+            continue;
+        }
+
+        // Markers float at eye height above the floor they were placed on
+        let floor = maze
+            .get(y as usize / BLOCK_SIZE)
+            .and_then(|row| row.get(x as usize / BLOCK_SIZE))
+            .map_or(0.0, |&cell| materials.get(cell).elevation);
+        let corrected_distance = distance * angle_diff.cos();
+        let block_pixels = BLOCK_SIZE as f32 * projection;
+        let center_y =
+            horizon - (floor + Player::EYE_HEIGHT - eye) * block_pixels / corrected_distance;
+
+        let screen_x = framebuffer.width as f32 / 2.0 + angle_diff.tan() * projection;
+        let size = (block_pixels / corrected_distance * 0.15).clamp(3.0, hh / 2.0);
+
+        // Diamond marker
+        let color = apply_light(WAYPOINT_COLOR, lighting.light_surface(x, y, distance, angle_diff));
+        framebuffer.set_current_color(fog.apply(color, corrected_distance));
+        framebuffer.fill_triangle(
+            (screen_x, center_y - size),
+            (screen_x - size / 2.0, center_y),
+            (screen_x + size / 2.0, center_y),
+        );
+        framebuffer.fill_triangle(
+            (screen_x, center_y + size),
+            (screen_x - size / 2.0, center_y),
+            (screen_x + size / 2.0, center_y),
+        );
+
+        labels.push((number, screen_x as i32, (center_y - size) as i32 - 20));
+    }
+
+    labels
+}
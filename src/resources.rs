@@ -1,30 +1,187 @@
 // src/resources.rs
+use crate::assets::{AssetError, AssetRoot};
+use crate::materials::{Material, MaterialRegistry};
+use crate::procedural::Placeholder;
 use raylib::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureFilter {
+    #[default]
+    Nearest,
+    Bilinear,
+}
+
+pub struct TextureLevel {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+impl TextureLevel {
+    // Wraps out-of-range coordinates so tiling textures repeat
+    fn texel(&self, x: i64, y: i64) -> Color {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    // Half-size copy, each texel the average of a 2x2 block
+    fn downsample(&self) -> TextureLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let block = [
+                    self.texel(2 * x as i64, 2 * y as i64),
+                    self.texel(2 * x as i64 + 1, 2 * y as i64),
+                    self.texel(2 * x as i64, 2 * y as i64 + 1),
+                    self.texel(2 * x as i64 + 1, 2 * y as i64 + 1),
+                ];
+                let average = |channel: fn(&Color) -> u8| {
+                    (block.iter().map(|c| channel(c) as u32).sum::<u32>() / 4) as u8
+                };
+                pixels.push(Color::new(
+                    average(|c| c.r),
+                    average(|c| c.g),
+                    average(|c| c.b),
+                    average(|c| c.a),
+                ));
+            }
+        }
+
+        TextureLevel { width, height, pixels }
+    }
+}
+
+// A texture of any size, with an optional mipmap chain (level 0 is the full image)
+pub struct Texture {
+    levels: Vec<TextureLevel>,
+}
+
+impl Texture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>, mipmaps: bool) -> Self {
+        let mut levels = vec![TextureLevel { width, height, pixels }];
+
+        if mipmaps {
+            while let Some(last) = levels.last() {
+                if last.width == 1 && last.height == 1 {
+                    break;
+                }
+                let next = last.downsample();
+                levels.push(next);
+            }
+        }
+
+        Self { levels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    // Sample with normalized UVs (wrapping). `lod` is log2 of how many texels land on one
+    // screen pixel; it is ignored when the texture has no mipmaps.
+    pub fn sample(&self, u: f32, v: f32, lod: f32, filter: TextureFilter) -> Color {
+        let level = (lod.max(0.0).round() as usize).min(self.levels.len() - 1);
+        let level = &self.levels[level];
+
+        let x = u * level.width as f32;
+        let y = v * level.height as f32;
+
+        match filter {
+            TextureFilter::Nearest => level.texel(x.floor() as i64, y.floor() as i64),
+            TextureFilter::Bilinear => {
+                // Blend the four texels around the sample point
+                let x = x - 0.5;
+                let y = y - 0.5;
+                let x0 = x.floor();
+                let y0 = y.floor();
+                let fx = x - x0;
+                let fy = y - y0;
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                let c00 = level.texel(x0, y0);
+                let c10 = level.texel(x0 + 1, y0);
+                let c01 = level.texel(x0, y0 + 1);
+                let c11 = level.texel(x0 + 1, y0 + 1);
+
+                let blend = |a: u8, b: u8, c: u8, d: u8| {
+                    let top = a as f32 + (b as f32 - a as f32) * fx;
+                    let bottom = c as f32 + (d as f32 - c as f32) * fx;
+                    (top + (bottom - top) * fy).round() as u8
+                };
+
+                Color::new(
+                    blend(c00.r, c10.r, c01.r, c11.r),
+                    blend(c00.g, c10.g, c01.g, c11.g),
+                    blend(c00.b, c10.b, c01.b, c11.b),
+                    blend(c00.a, c10.a, c01.a, c11.a),
+                )
+            }
+        }
+    }
+}
 
 pub struct Textures {
-    pub wall_brick: Vec<Color>,
-    pub wall_stone: Vec<Color>,
-    pub wall_metal: Vec<Color>,
-    pub texture_size: usize,
+    textures: HashMap<String, Texture>, // keyed by manifest path
+    pub filter: TextureFilter,
 }
 
 impl Textures {
-    pub fn new(_rl: &mut RaylibHandle, _thread: &RaylibThread) -> Self {
-        // Load images
-        let mut brick_img =
-            Image::load_image("textures/brick.png").expect("Failed to load brick.png");
-        let mut stone_img =
-            Image::load_image("textures/stone.png").expect("Failed to load stone.png");
-        let mut metal_img =
-            Image::load_image("textures/metal.png").expect("Failed to load metal.png");
+    const PLACEHOLDER_SIZE: usize = 64;
 
-        let texture_size = brick_img.width as usize;
+    pub fn new(
+        _rl: &mut RaylibHandle,
+        _thread: &RaylibThread,
+        materials: &MaterialRegistry,
+        assets: &AssetRoot,
+    ) -> Self {
+        let settings = materials.texture_settings();
+        let mut textures = HashMap::new();
 
-        let wall_brick = Self::image_to_color_array(&mut brick_img);
-        let wall_stone = Self::image_to_color_array(&mut stone_img);
-        let wall_metal = Self::image_to_color_array(&mut metal_img);
+        // Load every image the material manifest references, generating a placeholder
+        // for anything that is missing or unreadable
+        for path in materials.texture_paths() {
+            let texture = match Self::load_texture(&assets.resolve(path), settings.mipmaps) {
+                Ok(texture) => texture,
+                Err(err) => {
+                    println!("Warning: {}, using a placeholder", err);
+                    let size = Self::PLACEHOLDER_SIZE;
+                    let pixels = Placeholder::for_path(path).generate(size);
+                    Texture::new(size, size, pixels, settings.mipmaps)
+                }
+            };
+            textures.insert(path.to_string(), texture);
+        }
 
-        Self { wall_brick, wall_stone, wall_metal, texture_size }
+        Self { textures, filter: settings.filter }
+    }
+
+    pub fn load_texture(path: &Path, mipmaps: bool) -> Result<Texture, AssetError> {
+        if !path.is_file() {
+            return Err(AssetError::NotFound(path.to_path_buf()));
+        }
+
+        let mut img = Image::load_image(&path.to_string_lossy()).map_err(|err| {
+            AssetError::Image { path: path.to_path_buf(), message: err.to_string() }
+        })?;
+
+        Ok(Texture::new(
+            img.width as usize,
+            img.height as usize,
+            Self::image_to_color_array(&mut img),
+            mipmaps,
+        ))
     }
 
     fn image_to_color_array(img: &mut Image) -> Vec<Color> {
@@ -37,17 +194,28 @@ impl Textures {
         colors
     }
 
-    pub fn get_wall_texture(&self, wall_type: char) -> &Vec<Color> {
-        match wall_type {
-            '#' => &self.wall_stone,
-            '*' => &self.wall_metal,
-            '+' | '|' | '-' => &self.wall_brick,
-            _ => &self.wall_brick,
-        }
+    pub fn get(&self, path: &str) -> Option<&Texture> {
+        self.textures.get(path)
+    }
+
+    // Current frame for animated materials, `time` being the level clock
+    pub fn get_wall_texture(&self, material: &Material, time: f32) -> Option<&Texture> {
+        material.wall_texture_at(time).and_then(|path| self.get(path))
+    }
+
+    pub fn get_floor_texture(&self, material: &Material) -> Option<&Texture> {
+        material.floor_texture.as_deref().and_then(|path| self.get(path))
+    }
+
+    pub fn get_ceiling_texture(&self, material: &Material) -> Option<&Texture> {
+        material.ceiling_texture.as_deref().and_then(|path| self.get(path))
+    }
+
+    pub fn get_sprite_texture(&self, material: &Material) -> Option<&Texture> {
+        material.sprite.as_deref().and_then(|path| self.get(path))
     }
 
-    pub fn get_texture_pixel(&self, texture: &Vec<Color>, x: usize, y: usize) -> Color {
-        let index = (y * self.texture_size + x).min(texture.len() - 1);
-        texture[index]
+    pub fn sample(&self, texture: &Texture, u: f32, v: f32, lod: f32) -> Color {
+        texture.sample(u, v, lod, self.filter)
     }
 }
@@ -0,0 +1,352 @@
+// src/save.rs
+use crate::doors::DoorState;
+use crate::exploration::ExplorationMap;
+use crate::game_state::{GameManager, RespawnPoint};
+use crate::inventory::Inventory;
+use crate::json_file::{self, WriteError};
+use crate::pathfinding::Cell;
+use crate::player::Player;
+use crate::waypoints::Waypoints;
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SaveError {
+    #[error("failed to read {path}: {source}")]
+    Read { path: PathBuf, source: io::Error },
+    #[error(transparent)]
+    Write(#[from] WriteError),
+    #[error("invalid save file: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("save file version {0} is newer than this game understands")]
+    TooNew(u32),
+    #[error("the save is for a different maze")]
+    OtherLevel,
+    #[error("the saved maze isn't the size of the level")]
+    WrongSize,
+}
+
+// Upgrades from older save formats. Entry n turns a version n + 1 save into version n + 2,
+// so bumping the format means adding a step here; fields that are simply new can use
+// #[serde(default)] instead.
+const MIGRATIONS: [fn(&mut Map<String, Value>); 0] = [];
+
+// Where the player is and which way they face
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Pose {
+    pub x: f32,
+    pub y: f32,
+    pub a: f32,
+    pub elevation: f32,
+}
+
+// Everything needed to carry on a run: the player, the clock and whatever the level has
+// changed (doors, pushed walls, picked up keys, checkpoints)
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub level: String,     // Key of the maze as loaded, see records::level_key
+    pub maze: Vec<String>, // Current layout; pushwalls move cells around
+    pub player: Pose,
+    pub pitch: f32,
+    pub health: f32,
+    pub respawn: Pose,
+    pub level_time: f32,
+    pub secrets_found: usize,
+    pub exploration: ExplorationMap,
+    pub inventory: Inventory,
+    pub doors: Vec<(Cell, DoorState)>,
+    pub hidden_pushwalls: Vec<Cell>,
+    pub checkpoints: Vec<Cell>,
+    pub splits: Vec<f32>,
+    pub waypoints: Waypoints,
+    pub arrived_on: Option<Cell>, // Teleporter pad the player is standing on after using it
+}
+
+impl SaveGame {
+    pub const FILE_NAME: &'static str = "save.json";
+    pub const VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+
+    pub fn capture(level: &str, game: &GameManager, player: &Player, maze: &[Vec<char>]) -> Self {
+        let maze = game.pushwalls.settled(maze);
+        let respawn = Pose {
+            x: game.respawn.pos.x,
+            y: game.respawn.pos.y,
+            a: game.respawn.a,
+            elevation: game.respawn.elevation,
+        };
+        // Quitting on the death screen carries on from where respawning would have
+        let (pose, pitch, health) = if player.is_dead() {
+            (respawn, 0.0, Player::MAX_HEALTH)
+        } else {
+            let pose =
+                Pose { x: player.pos.x, y: player.pos.y, a: player.a, elevation: player.elevation };
+            (pose, player.pitch, player.health)
+        };
+        Self {
+            version: Self::VERSION,
+            level: level.to_string(),
+            maze: maze.iter().map(|row| row.iter().collect()).collect(),
+            player: pose,
+            pitch,
+            health,
+            respawn,
+            level_time: game.level_time,
+            secrets_found: game.stats.secrets_found,
+            exploration: game.exploration.clone(),
+            inventory: game.inventory.clone(),
+            doors: game.doors.states(),
+            hidden_pushwalls: game.pushwalls.hidden(),
+            checkpoints: game.checkpoints.reached_cells(),
+            splits: game.checkpoints.splits().to_vec(),
+            waypoints: game.waypoints.clone(),
+            arrived_on: if player.is_dead() { None } else { game.teleporters.arrived_on },
+        }
+    }
+
+    // Start playing from the save. The run starts over first, so anything the save doesn't
+    // mention is as it was at the level start.
+    pub fn apply(self, game: &mut GameManager, player: &mut Player, maze: &mut Vec<Vec<char>>) {
+        game.reset();
+        *maze = self.maze.iter().map(|row| row.chars().collect()).collect();
+
+        player.pos = Vector2::new(self.player.x, self.player.y);
+        player.a = self.player.a;
+        player.elevation = self.player.elevation;
+        player.pitch = self.pitch;
+        player.health = self.health.clamp(0.0, Player::MAX_HEALTH);
+
+        game.respawn = RespawnPoint {
+            pos: Vector2::new(self.respawn.x, self.respawn.y),
+            a: self.respawn.a,
+            elevation: self.respawn.elevation,
+        };
+        game.level_time = self.level_time;
+        game.stats.secrets_found = self.secrets_found;
+        if (self.exploration.width, self.exploration.height)
+            == (game.exploration.width, game.exploration.height)
+        {
+            game.exploration.merge(&self.exploration);
+        }
+        game.inventory = self.inventory;
+        game.doors.restore(&self.doors);
+        game.pushwalls.restore(&self.hidden_pushwalls);
+        game.checkpoints.restore(&self.checkpoints, &self.splits);
+        game.waypoints = self.waypoints;
+        game.teleporters.arrived_on = self.arrived_on;
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        Ok(json_file::save(path, self)?)
+    }
+
+    // Read a save for `level_maze`, whose key is `level`, upgrading it from an older
+    // version first. The saved maze has to have the level's shape, since everything else in
+    // the save indexes into it.
+    pub fn load(path: &Path, level: &str, level_maze: &[Vec<char>]) -> Result<Self, SaveError> {
+        let text = fs::read_to_string(path)
+            .map_err(|source| SaveError::Read { path: path.to_path_buf(), source })?;
+        let value = upgrade(serde_json::from_str(&text)?, &MIGRATIONS)?;
+
+        let save: Self = serde_json::from_value(value)?;
+        if save.level != level {
+            return Err(SaveError::OtherLevel);
+        }
+        let same_size = save.maze.len() == level_maze.len()
+            && save
+                .maze
+                .iter()
+                .zip(level_maze)
+                .all(|(saved, row)| saved.chars().count() == row.len());
+        if !same_size {
+            return Err(SaveError::WrongSize);
+        }
+        Ok(save)
+    }
+
+    // A finished run has nothing to continue
+    pub fn delete(path: &Path) -> Result<(), SaveError> {
+        match fs::remove_file(path) {
+            Err(source) if source.kind() != io::ErrorKind::NotFound => {
+                Err(WriteError { path: path.to_path_buf(), source }.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Bring a save from whatever version wrote it up to the one `migrations` ends at
+fn upgrade(
+    mut value: Value,
+    migrations: &[fn(&mut Map<String, Value>)],
+) -> Result<Value, SaveError> {
+    let latest = migrations.len() as u32 + 1;
+    if let Value::Object(fields) = &mut value {
+        let version = fields.get("version").and_then(Value::as_u64).unwrap_or(1) as u32;
+        if version > latest {
+            return Err(SaveError::TooNew(version));
+        }
+        for migrate in migrations.iter().skip(version.saturating_sub(1) as usize) {
+            migrate(fields);
+        }
+        fields.insert("version".to_string(), latest.into());
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::MaterialRegistry;
+    use pretty_assertions::assert_eq;
+
+    const BLOCK: usize = 64;
+    const LEVEL: &str = "test level";
+
+    // Start, red key, a door, a checkpoint and the goal in one corridor
+    fn level() -> Vec<Vec<char>> {
+        ["#######", "#prDcg#", "#######"].iter().map(|row| row.chars().collect()).collect()
+    }
+
+    fn new_game(maze: &Vec<Vec<char>>, materials: &MaterialRegistry) -> (GameManager, Player) {
+        let player = Player::from_maze(maze, BLOCK).expect("level has a start");
+        let mut game = GameManager::new(maze, materials, &player, BLOCK);
+        game.reset();
+        (game, player)
+    }
+
+    fn stand_on(player: &mut Player, (col, row): Cell) {
+        let block = BLOCK as f32;
+        player.pos = Vector2::new((col as f32 + 0.5) * block, (row as f32 + 0.5) * block);
+    }
+
+    // A save for a fresh run, as JSON, written where `load` can read it
+    fn write_save(name: &str, edit: impl FnOnce(&mut Map<String, Value>)) -> PathBuf {
+        let materials = MaterialRegistry::builtin();
+        let maze = level();
+        let (game, player) = new_game(&maze, &materials);
+        let mut value = serde_json::to_value(SaveGame::capture(LEVEL, &game, &player, &maze))
+            .expect("save serializes");
+        edit(value.as_object_mut().expect("save is an object"));
+
+        let path = std::env::temp_dir().join(format!(
+            "maze-save-test-{}-{}.json",
+            std::process::id(),
+            name
+        ));
+        fs::write(&path, value.to_string()).expect("temp dir is writable");
+        path
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let path = write_save("too-new", |fields| {
+            fields.insert("version".to_string(), (SaveGame::VERSION + 1).into());
+        });
+        let result = SaveGame::load(&path, LEVEL, &level());
+        fs::remove_file(&path).ok();
+        assert!(
+            matches!(result, Err(SaveError::TooNew(version)) if version == SaveGame::VERSION + 1)
+        );
+    }
+
+    #[test]
+    fn missing_version_loads_as_version_1() {
+        let path = write_save("unversioned", |fields| {
+            fields.remove("version");
+        });
+        let result = SaveGame::load(&path, LEVEL, &level());
+        fs::remove_file(&path).ok();
+        assert_eq!(result.expect("unversioned save loads").version, SaveGame::VERSION);
+    }
+
+    #[test]
+    fn save_for_another_level_is_rejected() {
+        let path = write_save("other-level", |_| {});
+        let result = SaveGame::load(&path, "some other level", &level());
+        fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(SaveError::OtherLevel)));
+    }
+
+    #[test]
+    fn save_of_another_size_is_rejected() {
+        let path = write_save("wrong-size", |fields| {
+            fields.insert("maze".to_string(), serde_json::json!(["#######", "#p   g#"]));
+        });
+        let result = SaveGame::load(&path, LEVEL, &level());
+        fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(SaveError::WrongSize)));
+    }
+
+    #[test]
+    fn migrations_run_from_the_saved_version_on() {
+        // Each step notes that it ran
+        let migrations: [fn(&mut Map<String, Value>); 2] =
+            [|fields| push_step(fields, 2), |fields| push_step(fields, 3)];
+        fn push_step(fields: &mut Map<String, Value>, version: u32) {
+            let steps = fields.entry("steps").or_insert_with(|| Value::Array(Vec::new()));
+            steps.as_array_mut().expect("steps is an array").push(version.into());
+        }
+
+        let from_1 = upgrade(serde_json::json!({}), &migrations).expect("version 1 upgrades");
+        assert_eq!(from_1, serde_json::json!({ "version": 3, "steps": [2, 3] }));
+        let from_2 =
+            upgrade(serde_json::json!({ "version": 2 }), &migrations).expect("version 2 upgrades");
+        assert_eq!(from_2, serde_json::json!({ "version": 3, "steps": [3] }));
+        let latest = upgrade(serde_json::json!({ "version": 3 }), &migrations)
+            .expect("current version loads");
+        assert_eq!(latest, serde_json::json!({ "version": 3 }));
+    }
+
+    #[test]
+    fn round_trip_keeps_the_run() {
+        let materials = MaterialRegistry::builtin();
+        let maze = level();
+        let (mut game, mut player) = new_game(&maze, &materials);
+
+        stand_on(&mut player, (2, 1));
+        game.inventory.pick_up(&player, &maze, &materials, BLOCK);
+        let open = DoorState { openness: 1.0, opening: true, open_time: 0.5 };
+        game.doors.restore(&[((3, 1), open)]);
+        stand_on(&mut player, (4, 1));
+        game.level_time = 12.5;
+        assert!(game.update_checkpoints(&player, BLOCK));
+
+        let text = serde_json::to_string(&SaveGame::capture(LEVEL, &game, &player, &maze))
+            .expect("save serializes");
+        let save: SaveGame = serde_json::from_str(&text).expect("save parses");
+
+        let mut loaded_maze = level();
+        let (mut loaded, mut loaded_player) = new_game(&loaded_maze, &materials);
+        save.apply(&mut loaded, &mut loaded_player, &mut loaded_maze);
+
+        assert_eq!((loaded_player.pos.x, loaded_player.pos.y), (player.pos.x, player.pos.y));
+        assert_eq!(loaded.level_time, 12.5);
+        assert!(loaded.inventory.has("red"));
+        assert!(loaded.inventory.is_collected(2, 1));
+        let door = loaded.doors.get(3, 1).expect("door survives");
+        assert_eq!(door.openness, 1.0);
+        assert_eq!(loaded.checkpoints.reached(), 1);
+        assert_eq!(loaded.checkpoints.splits(), [12.5]);
+        assert_eq!((loaded.respawn.pos.x, loaded.respawn.pos.y), (player.pos.x, player.pos.y));
+    }
+
+    #[test]
+    fn dying_saves_the_respawn_point() {
+        let materials = MaterialRegistry::builtin();
+        let maze = level();
+        let (game, mut player) = new_game(&maze, &materials);
+        let start = player.pos;
+
+        stand_on(&mut player, (4, 1));
+        player.health = 0.0;
+        let save = SaveGame::capture(LEVEL, &game, &player, &maze);
+
+        assert_eq!((save.player.x, save.player.y), (start.x, start.y));
+        assert_eq!(save.health, Player::MAX_HEALTH);
+    }
+}
@@ -0,0 +1,79 @@
+// src/settings.rs
+use crate::json_file::{self, WriteError};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::thread;
+
+// User preferences kept between runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub render_scale: f32,     // 3D view resolution relative to the window
+    pub render_threads: usize, // Threads for the 3D view, 0 uses every core
+    pub fov_degrees: f32,      // Horizontal field of view
+    pub player_name: String,   // Shown on the best-times board
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        // The login name is a better guess than nothing
+        let player_name = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "Player".to_string());
+        Self { render_scale: 1.0, render_threads: 0, fov_degrees: 60.0, player_name }
+    }
+}
+
+impl Settings {
+    pub const FILE_NAME: &'static str = "settings.json";
+    pub const MIN_RENDER_SCALE: f32 = 0.25;
+    pub const MAX_RENDER_SCALE: f32 = 2.0;
+    pub const RENDER_SCALE_STEP: f32 = 0.25;
+    pub const MIN_FOV: f32 = 40.0;
+    pub const MAX_FOV: f32 = 120.0;
+    pub const FOV_STEP: f32 = 5.0;
+
+    pub fn load(path: &Path) -> Self {
+        let mut settings: Self = json_file::load_or_default(path, "settings");
+        settings.set_render_scale(settings.render_scale);
+        settings.set_fov(settings.fov_degrees);
+        settings
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), WriteError> {
+        json_file::save(path, self)
+    }
+
+    pub fn set_render_scale(&mut self, scale: f32) {
+        self.render_scale = scale.clamp(Self::MIN_RENDER_SCALE, Self::MAX_RENDER_SCALE);
+    }
+
+    pub fn adjust_render_scale(&mut self, steps: f32) {
+        self.set_render_scale(self.render_scale + steps * Self::RENDER_SCALE_STEP);
+    }
+
+    pub fn set_fov(&mut self, degrees: f32) {
+        self.fov_degrees = degrees.clamp(Self::MIN_FOV, Self::MAX_FOV);
+    }
+
+    pub fn adjust_fov(&mut self, steps: f32) {
+        self.set_fov(self.fov_degrees + steps * Self::FOV_STEP);
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov_degrees.to_radians()
+    }
+
+    // Size of the 3D view for a window, never below one pixel
+    pub fn render_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let scale = |size: u32| ((size as f32 * self.render_scale).round() as u32).max(1);
+        (scale(width), scale(height))
+    }
+
+    pub fn render_threads(&self) -> usize {
+        match self.render_threads {
+            0 => thread::available_parallelism().map_or(1, |count| count.get()),
+            count => count,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// src/teleporters.rs
+use crate::materials::MaterialRegistry;
+use crate::pathfinding::Cell;
+use crate::player::Player;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+// Teleporter pads link in pairs by glyph. Returns each pad's partner; glyphs that don't
+// appear exactly twice are left out (the level validator reports them).
+pub fn pair_pads(maze: &[Vec<char>], materials: &MaterialRegistry) -> HashMap<Cell, Cell> {
+    let mut pads: HashMap<char, Vec<Cell>> = HashMap::new();
+    for (row, cells) in maze.iter().enumerate() {
+        for (col, &glyph) in cells.iter().enumerate() {
+            if materials.get(glyph).teleporter {
+                pads.entry(glyph).or_default().push((col, row));
+            }
+        }
+    }
+
+    let mut links = HashMap::new();
+    for cells in pads.values() {
+        if let &[a, b] = cells.as_slice() {
+            links.insert(a, b);
+            links.insert(b, a);
+        }
+    }
+    links
+}
+
+// Moves the player between linked pads
+pub struct Teleporters {
+    links: HashMap<Cell, Cell>,
+    pub arrived_on: Option<Cell>, // Pad the player came out of; inactive until they step off it
+}
+
+impl Teleporters {
+    pub fn new(maze: &[Vec<char>], materials: &MaterialRegistry) -> Self {
+        Self { links: pair_pads(maze, materials), arrived_on: None }
+    }
+
+    pub fn reset(&mut self) {
+        self.arrived_on = None;
+    }
+
+    // Send the player through the pad they are standing on. Returns whether they moved.
+    pub fn update(
+        &mut self,
+        player: &mut Player,
+        maze: &[Vec<char>],
+        materials: &MaterialRegistry,
+        block_size: usize,
+    ) -> bool {
+        let cell = (player.pos.x as usize / block_size, player.pos.y as usize / block_size);
+        if self.arrived_on == Some(cell) {
+            return false;
+        }
+        self.arrived_on = None;
+
+        let Some(&(col, row)) = self.links.get(&cell) else {
+            return false;
+        };
+        let block = block_size as f32;
+        player.pos.x = (col as f32 + 0.5) * block;
+        player.pos.y = (row as f32 + 0.5) * block;
+        player.a = exit_angle(player.a, (col, row), maze, materials);
+        player.elevation = materials.get(maze[row][col]).elevation;
+        self.arrived_on = Some((col, row));
+        true
+    }
+}
+
+// Keep heading the way the player was going if the pad opens that way, otherwise face
+// its first open side
+fn exit_angle(a: f32, (col, row): Cell, maze: &[Vec<char>], materials: &MaterialRegistry) -> f32 {
+    // East, south, west, north, as (step, angle) with y pointing down the maze
+    const SIDES: [((i64, i64), f32); 4] =
+        [((1, 0), 0.0), ((0, 1), PI / 2.0), ((-1, 0), PI), ((0, -1), -PI / 2.0)];
+
+    let heading = (a / (PI / 2.0)).round().rem_euclid(4.0) as usize;
+    let open = |(dx, dy): (i64, i64)| {
+        let (x, y) = (col as i64 + dx, row as i64 + dy);
+        usize::try_from(y)
+            .ok()
+            .zip(usize::try_from(x).ok())
+            .and_then(|(y, x)| maze.get(y).and_then(|cells| cells.get(x)))
+            .is_some_and(|&glyph| {
+                let material = materials.get(glyph);
+                material.walkable && !material.teleporter
+            })
+    };
+
+    (0..SIDES.len())
+        .map(|turn| SIDES[(heading + turn) % SIDES.len()])
+        .find(|&(step, _)| open(step))
+        .map_or(a, |(_, angle)| angle)
+}
@@ -0,0 +1,184 @@
+// src/validator.rs
+use crate::materials::MaterialRegistry;
+use crate::pathfinding::{Cell, NavGraph};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LevelProblem {
+    #[error("the maze has no start cell 'p'")]
+    NoStart,
+    #[error("the maze has no goal cell 'g'")]
+    NoGoal,
+    #[error("the {key} door at ({col}, {row}) has no {key} key anywhere in the maze")]
+    MissingKey { key: String, col: usize, row: usize },
+    #[error("teleporter '{glyph}' appears {count} time(s); pads must come in pairs")]
+    UnpairedTeleporter { glyph: char, count: usize },
+    #[error("the goal can't be reached from the start{}", blocked_by(.locked))]
+    Unsolvable { locked: Vec<String> },
+}
+
+fn blocked_by(locked: &[String]) -> String {
+    if locked.is_empty() {
+        String::new()
+    } else {
+        format!("; stuck behind doors needing: {}", locked.join(", "))
+    }
+}
+
+// Checks the level can be finished. Starting with no keys, the player walks everywhere
+// they can, picks up every key in reach and opens the doors those keys unlock, until the
+// goal is reached or nothing new opens up. A locked door on the only way to the goal is
+// fine as long as its key can be reached first.
+pub fn validate(maze: &Vec<Vec<char>>, materials: &MaterialRegistry) -> Vec<LevelProblem> {
+    let graph = NavGraph::new(maze, materials);
+    let mut problems = Vec::new();
+
+    let cells = || {
+        maze.iter().enumerate().flat_map(|(row, cells)| {
+            cells.iter().enumerate().map(move |(col, &glyph)| ((col, row), glyph))
+        })
+    };
+    let find = |wanted: char| cells().find(|&(_, glyph)| glyph == wanted).map(|(cell, _)| cell);
+    let lock =
+        |cell: Cell| graph.glyph(cell).and_then(|glyph| materials.get(glyph).lock.as_deref());
+    let key = |cell: Cell| graph.glyph(cell).and_then(|glyph| materials.get(glyph).key.as_deref());
+
+    let keys: HashSet<&str> = cells().filter_map(|(cell, _)| key(cell)).collect();
+    for ((col, row), _) in cells() {
+        if let Some(needed) = lock((col, row))
+            && !keys.contains(needed)
+        {
+            problems.push(LevelProblem::MissingKey { key: needed.to_string(), col, row });
+        }
+    }
+
+    let mut pads: BTreeMap<char, usize> = BTreeMap::new();
+    for (_, glyph) in cells().filter(|&(_, glyph)| materials.get(glyph).teleporter) {
+        *pads.entry(glyph).or_default() += 1;
+    }
+    for (glyph, count) in pads {
+        if count != 2 {
+            problems.push(LevelProblem::UnpairedTeleporter { glyph, count });
+        }
+    }
+
+    let start = find('p');
+    let goal = find('g');
+    if start.is_none() {
+        problems.push(LevelProblem::NoStart);
+    }
+    if goal.is_none() {
+        problems.push(LevelProblem::NoGoal);
+    }
+    let (Some(start), Some(goal)) = (start, goal) else {
+        return problems;
+    };
+
+    let mut held: HashSet<&str> = HashSet::new();
+    loop {
+        let reached =
+            graph.reachable(start, |cell| lock(cell).is_none_or(|needed| held.contains(needed)));
+        if reached.contains(&goal) {
+            break;
+        }
+
+        let found: Vec<&str> =
+            reached.iter().filter_map(|&cell| key(cell)).filter(|k| !held.contains(k)).collect();
+        if found.is_empty() {
+            // Report the locks on the edge of where the player can get to
+            let locked: BTreeSet<&str> = reached
+                .iter()
+                .flat_map(|&cell| graph.neighbours(cell))
+                .filter_map(lock)
+                .filter(|needed| !held.contains(needed))
+                .collect();
+            let locked = locked.into_iter().map(str::to_string).collect();
+            problems.push(LevelProblem::Unsolvable { locked });
+            break;
+        }
+        held.extend(found);
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    fn problems(rows: &[&str]) -> Vec<LevelProblem> {
+        validate(&maze(rows), &MaterialRegistry::builtin())
+    }
+
+    #[test]
+    fn key_behind_its_own_door_is_unsolvable() {
+        let found = problems(&["########", "#p Rr g#", "########"]);
+        assert!(
+            matches!(found.as_slice(), [LevelProblem::Unsolvable { locked }] if locked == &["red"]),
+            "{:?}",
+            found
+        );
+    }
+
+    #[test]
+    fn key_before_its_door_is_solvable() {
+        let found = problems(&["#########", "#p r R g#", "#########"]);
+        assert!(found.is_empty(), "{:?}", found);
+    }
+
+    #[test]
+    fn door_without_a_key_is_reported() {
+        let found = problems(&["######", "#pYg #", "######"]);
+        assert!(
+            found.iter().any(|problem| matches!(
+                problem,
+                LevelProblem::MissingKey { key, col: 2, row: 1 } if key == "yellow"
+            )),
+            "{:?}",
+            found
+        );
+        assert!(found.iter().any(|problem| matches!(problem, LevelProblem::Unsolvable { .. })));
+    }
+
+    #[test]
+    fn lone_teleporter_is_reported() {
+        let found = problems(&["#####", "#p1g#", "#####"]);
+        assert!(
+            matches!(found.as_slice(), [LevelProblem::UnpairedTeleporter { glyph: '1', count: 1 }]),
+            "{:?}",
+            found
+        );
+    }
+
+    #[test]
+    fn goal_reachable_only_by_teleporter_is_solvable() {
+        let found = problems(&["#######", "#p1#1g#", "#######"]);
+        assert!(found.is_empty(), "{:?}", found);
+    }
+
+    #[test]
+    fn goal_walled_off_is_unsolvable() {
+        let found = problems(&["######", "#p#g #", "######"]);
+        assert!(
+            matches!(found.as_slice(), [LevelProblem::Unsolvable { locked }] if locked.is_empty()),
+            "{:?}",
+            found
+        );
+    }
+
+    #[test]
+    fn missing_start_and_goal_are_reported() {
+        let found = problems(&["####", "#  #", "####"]);
+        assert!(matches!(found.as_slice(), [LevelProblem::NoStart, LevelProblem::NoGoal]));
+    }
+
+    #[test]
+    fn goal_behind_a_pushwall_is_reachable() {
+        let found = problems(&["#######", "#p %g #", "#######"]);
+        assert!(found.is_empty(), "{:?}", found);
+    }
+}
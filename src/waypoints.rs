@@ -0,0 +1,55 @@
+// src/waypoints.rs
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_WAYPOINTS: usize = 9;
+pub const WAYPOINT_COLOR: Color = Color::SKYBLUE;
+
+// Numbered markers placed from the full-screen map. Slots keep their number when
+// another waypoint is removed.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Waypoints {
+    slots: [Option<(f32, f32)>; MAX_WAYPOINTS],
+}
+
+impl Waypoints {
+    pub fn new() -> Self {
+        Self { slots: [None; MAX_WAYPOINTS] }
+    }
+
+    pub fn clear(&mut self) {
+        self.slots = [None; MAX_WAYPOINTS];
+    }
+
+    // Place a waypoint in the lowest free slot, returning its number (1-based)
+    pub fn place(&mut self, x: f32, y: f32) -> Option<usize> {
+        let index = self.slots.iter().position(|slot| slot.is_none())?;
+        self.slots[index] = Some((x, y));
+        Some(index + 1)
+    }
+
+    // Remove the closest waypoint within `radius` world units of (x, y)
+    pub fn remove_near(&mut self, x: f32, y: f32, radius: f32) -> bool {
+        let closest = self
+            .iter()
+            .map(|(number, wx, wy)| (number, (wx - x).powi(2) + (wy - y).powi(2)))
+            .filter(|&(_, dist_sq)| dist_sq <= radius * radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        match closest {
+            Some((number, _)) => {
+                self.slots[number - 1] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // (number, world x, world y) for every placed waypoint
+    pub fn iter(&self) -> impl Iterator<Item = (usize, f32, f32)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.map(|(x, y)| (index + 1, x, y)))
+    }
+}